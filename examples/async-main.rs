@@ -0,0 +1,43 @@
+//! Runs an application within a program that already runs a tokio runtime, spawning its tasks
+//! onto that runtime.
+
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    text::Text,
+};
+use ratatui_elm::{AppWithBackend, Outcome, Task, Tokio, Update, backend::CrosstermBackend};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), ratatui_elm::Error> {
+    let mut app = AppWithBackend::<Tokio, CrosstermBackend>::with_spawner(|task| {
+        tokio::spawn(task);
+    })
+    .build_with(0, update, view)
+    .with_init(|_| Outcome::none().task(Task::perform(tick())));
+    app.run_async().await?;
+    Ok(())
+}
+
+async fn tick() {
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+fn update(state: &mut u64, update: Update<(), Event>) -> Outcome<()> {
+    match update {
+        Update::Message(()) => {
+            *state += 1;
+            Outcome::render().task(Task::perform(tick()))
+        }
+        Update::Terminal(Event::Key(KeyEvent {
+            code: KeyCode::Char('q') | KeyCode::Esc,
+            ..
+        })) => Outcome::quit(),
+        Update::Terminal(_) => Outcome::none(),
+    }
+}
+
+fn view(state: &mut u64, frame: &mut Frame) {
+    frame.render_widget(Text::raw(format!("Ticks: {state}")), frame.area());
+}
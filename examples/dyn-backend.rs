@@ -0,0 +1,60 @@
+//! Shows the last few terminal events, with the backend chosen at runtime.
+//!
+//! Choose the backend with the first argument or the `RATATUI_ELM_BACKEND` environment variable,
+//! as in `cargo run --example dyn-backend --features termwiz -- termwiz`.
+
+use ratatui::{
+    Frame,
+    text::{Line, Text},
+};
+use ratatui_elm::{
+    AppWithBackend, Outcome, Tokio, Update,
+    backend::{BackendKind, DynBackend, TerminalOptions},
+    input::{Event, Key, KeyCode, Modifiers},
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let kind = match std::env::args()
+        .nth(1)
+        .or_else(|| std::env::var("RATATUI_ELM_BACKEND").ok())
+    {
+        Some(name) => name.parse()?,
+        None => BackendKind::default(),
+    };
+    AppWithBackend::<Tokio, DynBackend>::new(update, view)?
+        .terminal_options(TerminalOptions {
+            mouse: true,
+            paste: true,
+            focus: true,
+            ..Default::default()
+        })
+        .backend(kind)
+        .with_init(move |events: &mut Vec<String>| {
+            events.push(format!("Running with {kind}, press Ctrl+C to quit"));
+            Outcome::<()>::none()
+        })
+        .run()?;
+    Ok(())
+}
+
+#[allow(clippy::ptr_arg, reason = "required for type inference to pass")]
+fn view(events: &mut Vec<String>, frame: &mut Frame) {
+    let lines = events.iter().rev().take(frame.area().height.into()).rev();
+    frame.render_widget(Text::from_iter(lines.map(Line::raw)), frame.area());
+}
+
+// The same code handles the events of every backend.
+fn update(events: &mut Vec<String>, update: Update<(), Event>) -> Outcome<()> {
+    match update {
+        Update::Terminal(Event::Key(Key {
+            code: KeyCode::Char('c'),
+            modifiers,
+            ..
+        })) if modifiers.contains(Modifiers::CONTROL) => Outcome::quit(),
+        Update::Terminal(event) => {
+            events.push(format!("{event:?}"));
+            Outcome::render()
+        }
+        Update::Message(()) => Outcome::none(),
+    }
+}
@@ -6,10 +6,9 @@ use ratatui::{
 };
 use ratatui_elm::{Task, Tokio, Update, backend::CrosstermBackend};
 
-fn main() {
-    ratatui_elm::AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)
-        .run()
-        .unwrap();
+fn main() -> Result<(), ratatui_elm::Error> {
+    ratatui_elm::AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)?.run()?;
+    Ok(())
 }
 
 fn update(_state: &mut (), event: Update<(), Event>) -> (Task<()>, bool) {
@@ -8,10 +8,9 @@ use ratatui_elm::{
     backend::{TermionBackend, termion::Event},
 };
 
-fn main() {
-    ratatui_elm::AppWithBackend::<Tokio, TermionBackend>::new(update, view)
-        .run()
-        .unwrap();
+fn main() -> Result<(), ratatui_elm::Error> {
+    ratatui_elm::AppWithBackend::<Tokio, TermionBackend>::new(update, view)?.run()?;
+    Ok(())
 }
 
 fn update(_state: &mut (), event: Update<(), Event>) -> (Task<()>, bool) {
@@ -1,15 +1,13 @@
 use ratatui::{
-    backend::TermwizBackend,
     termwiz::input::{InputEvent, KeyCode, KeyEvent},
     text::Text,
     widgets::{Block, Borders},
 };
-use ratatui_elm::{Task, Tokio, Update};
+use ratatui_elm::{Task, Tokio, Update, backend::TermwizBackend};
 
-fn main() {
-    ratatui_elm::AppWithBackend::<Tokio, TermwizBackend>::new(update, view)
-        .run()
-        .unwrap();
+fn main() -> Result<(), ratatui_elm::Error> {
+    ratatui_elm::AppWithBackend::<Tokio, TermwizBackend>::new(update, view)?.run()?;
+    Ok(())
 }
 
 fn update(_state: &mut (), event: Update<(), InputEvent>) -> (Task<()>, bool) {
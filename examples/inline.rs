@@ -0,0 +1,52 @@
+//! A progress bar drawn under the prompt, which prints a line above itself for every finished
+//! step.
+
+use byor::time::Time;
+use futures::{StreamExt, stream};
+use ratatui::{
+    Frame, Viewport,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    widgets::Gauge,
+};
+use ratatui_elm::{AppWithBackend, Outcome, Task, Tokio, Update, backend::CrosstermBackend};
+use std::time::Duration;
+
+const STEPS: u16 = 10;
+
+fn main() -> Result<(), ratatui_elm::Error> {
+    AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)?
+        .viewport(Viewport::Inline(1))
+        .subscription(stream::iter(1..=STEPS).then(|step| async move {
+            Tokio::sleep(Duration::from_millis(300)).await;
+            step
+        }))
+        .run()?;
+    Ok(())
+}
+
+fn update(state: &mut u16, update: Update<u16, Event>) -> Outcome<u16> {
+    match update {
+        Update::Message(step) => {
+            *state = step;
+            let outcome =
+                Outcome::render().task(Task::insert_before(format!("Finished step {state}")));
+            if *state == STEPS {
+                outcome.task(Task::Quit)
+            } else {
+                outcome
+            }
+        }
+        Update::Terminal(Event::Key(KeyEvent {
+            code: KeyCode::Char('q') | KeyCode::Esc,
+            ..
+        })) => Outcome::quit(),
+        Update::Terminal(_) => Outcome::none(),
+    }
+}
+
+fn view(state: &mut u16, frame: &mut Frame) {
+    let gauge = Gauge::default()
+        .ratio(f64::from(*state) / f64::from(STEPS))
+        .label(format!("{state}/{STEPS}"));
+    frame.render_widget(gauge, frame.area());
+}
@@ -3,12 +3,20 @@ use ratatui::{
     crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
     text::{Line, Text},
 };
-use ratatui_elm::{AppWithBackend, Task, Tokio, Update, backend::CrosstermBackend};
+use ratatui_elm::{
+    AppWithBackend, Task, Tokio, Update,
+    backend::{CrosstermBackend, TerminalOptions},
+};
 
-fn main() {
-    AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)
-        .run()
-        .unwrap();
+fn main() -> Result<(), ratatui_elm::Error> {
+    AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)?
+        // Report key releases and keys like Ctrl+Enter, on terminals that support it.
+        .terminal_options(TerminalOptions {
+            keyboard_enhancement: true,
+            ..Default::default()
+        })
+        .run()?;
+    Ok(())
 }
 
 #[allow(clippy::ptr_arg, reason = "required for type inference to pass")]
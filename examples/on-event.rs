@@ -0,0 +1,47 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    text::Text,
+};
+use ratatui_elm::{AppWithBackend, Exit, Messages, Outcome, Tokio, backend::CrosstermBackend};
+
+enum Message {
+    Increment,
+    Decrement,
+    Quit,
+}
+
+fn main() -> Result<(), ratatui_elm::Error> {
+    let count = AppWithBackend::<Tokio, CrosstermBackend>::new(Messages(update), view)?
+        .on_event(on_event)
+        .run()?;
+    if let Exit::Value(count) = count {
+        println!("Final count: {count}");
+    }
+    Ok(())
+}
+
+fn on_event(event: &Event, _state: &i64) -> Option<Message> {
+    let Event::Key(KeyEvent { code, .. }) = event else {
+        return None;
+    };
+    match code {
+        KeyCode::Up | KeyCode::Char('+') => Some(Message::Increment),
+        KeyCode::Down | KeyCode::Char('-') => Some(Message::Decrement),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Message::Quit),
+        _ => None,
+    }
+}
+
+fn update(state: &mut i64, message: Message) -> Outcome<Message, i64> {
+    match message {
+        Message::Increment => *state += 1,
+        Message::Decrement => *state -= 1,
+        Message::Quit => return Outcome::quit_with(*state),
+    }
+    Outcome::render()
+}
+
+fn view(state: &mut i64, frame: &mut Frame) {
+    frame.render_widget(Text::raw(format!("Count: {state}")), frame.area());
+}
@@ -0,0 +1,50 @@
+//! The same counter as the `on-event` example, with its keys matched in the same form for every
+//! backend. Only the backend's type would change to run it on crossterm or termwiz.
+
+use ratatui::{Frame, text::Text};
+use ratatui_elm::{
+    AppWithBackend, Exit, Messages, Outcome, Tokio,
+    backend::TermionBackend,
+    input::{Event, Key, KeyCode},
+};
+
+enum Message {
+    Increment,
+    Decrement,
+    Quit,
+}
+
+fn main() -> Result<(), ratatui_elm::Error> {
+    let count = AppWithBackend::<Tokio, TermionBackend>::new(Messages(update), view)?
+        .on_input(on_input)
+        .run()?;
+    if let Exit::Value(count) = count {
+        println!("Final count: {count}");
+    }
+    Ok(())
+}
+
+fn on_input(event: &Event, _state: &i64) -> Option<Message> {
+    let Event::Key(Key { code, .. }) = event else {
+        return None;
+    };
+    match code {
+        KeyCode::Up | KeyCode::Char('+') => Some(Message::Increment),
+        KeyCode::Down | KeyCode::Char('-') => Some(Message::Decrement),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Message::Quit),
+        _ => None,
+    }
+}
+
+fn update(state: &mut i64, message: Message) -> Outcome<Message, i64> {
+    match message {
+        Message::Increment => *state += 1,
+        Message::Decrement => *state -= 1,
+        Message::Quit => return Outcome::quit_with(*state),
+    }
+    Outcome::render()
+}
+
+fn view(state: &mut i64, frame: &mut Frame) {
+    frame.render_widget(Text::raw(format!("Count: {state}")), frame.area());
+}
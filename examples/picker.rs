@@ -0,0 +1,56 @@
+//! Prints the picked fruit to standard output, so that it can be captured with
+//! `fruit=$(cargo run --example picker)`. The interface is rendered to standard error to keep it
+//! out of the way.
+
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    style::{Style, Stylize},
+    widgets::{List, ListState},
+};
+#[cfg(unix)]
+use ratatui_elm::backend::RenderTarget;
+use ratatui_elm::{AppWithBackend, Exit, Outcome, Tokio, Update, backend::CrosstermBackend};
+
+const FRUITS: [&str; 5] = ["Apple", "Banana", "Cherry", "Durian", "Elderberry"];
+
+fn main() -> Result<Exit<()>, ratatui_elm::Error> {
+    let mut app = AppWithBackend::<Tokio, CrosstermBackend>::new_with(
+        ListState::default().with_selected(Some(0)),
+        update,
+        view,
+    )?;
+    // Only unix can render anywhere but standard output.
+    #[cfg(unix)]
+    {
+        app = app.render_to(RenderTarget::Stderr);
+    }
+    let exit = app.run()?;
+    if let Exit::Value(fruit) = &exit {
+        println!("{fruit}");
+    }
+    // Exits successfully unless the terminal's input was closed.
+    Ok(exit.map(|_| ()))
+}
+
+fn update(state: &mut ListState, update: Update<(), Event>) -> Outcome<(), &'static str> {
+    let Update::Terminal(Event::Key(KeyEvent { code, .. })) = update else {
+        return Outcome::none();
+    };
+    match code {
+        KeyCode::Up => state.select_previous(),
+        KeyCode::Down => state.select_next(),
+        KeyCode::Enter => {
+            let fruit = FRUITS[state.selected().unwrap_or(0).min(FRUITS.len() - 1)];
+            return Outcome::quit_with(fruit);
+        }
+        KeyCode::Char('q') | KeyCode::Esc => return Outcome::quit(),
+        _ => return Outcome::none(),
+    }
+    Outcome::render()
+}
+
+fn view(state: &mut ListState, frame: &mut Frame) {
+    let list = List::new(FRUITS).highlight_style(Style::new().reversed());
+    frame.render_stateful_widget(list, frame.area(), state);
+}
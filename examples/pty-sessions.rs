@@ -0,0 +1,192 @@
+//! Serves a separate session of the same application to every client of a Unix socket, each in a
+//! pseudoterminal of its own.
+//!
+//! Run the example, then connect to it from as many terminals as you like with:
+//!
+//! ```sh
+//! socat -,raw,echo=0 UNIX-CONNECT:/tmp/ratatui-elm-sessions.sock
+//! ```
+//!
+//! The application runs on the follower end of each pseudoterminal, and the bytes on its leader
+//! end are copied to and from the client. Run the example with `--no-pty` to hand the socket to the
+//! application directly instead, with `Tty::from_io`.
+//!
+//! A real server would also tell each session about the size of its client's terminal with
+//! `Tty::set_size`. Here every session is 80x24.
+
+use ratatui::{
+    Frame,
+    termion::event::{Event as TermionEvent, Key},
+    text::Text,
+    widgets::{Block, Borders},
+};
+use ratatui_elm::{
+    AppHandle, AppWithBackend, Outcome, Tokio, Update,
+    backend::{TermionBackend, Tty, termion::Event},
+};
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::{
+        fd::{FromRawFd, OwnedFd},
+        unix::net::{UnixListener, UnixStream},
+    },
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+use tokio::runtime::Runtime;
+
+const SOCKET: &str = "/tmp/ratatui-elm-sessions.sock";
+
+struct Session {
+    id: usize,
+    presses: u64,
+    connected: Arc<AtomicUsize>,
+}
+
+fn main() -> io::Result<()> {
+    let _ = std::fs::remove_file(SOCKET);
+    let listener = UnixListener::bind(SOCKET)?;
+    println!("Listening on {SOCKET}");
+
+    // Every session shares one runtime.
+    let runtime = Arc::new(Runtime::new()?);
+    let connected = Arc::new(AtomicUsize::new(0));
+    for (id, client) in listener.incoming().enumerate() {
+        let client = client?;
+        let runtime = runtime.clone();
+        let connected = connected.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve(id + 1, client, runtime, connected) {
+                eprintln!("Session {} failed: {e}", id + 1);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve(
+    id: usize,
+    client: UnixStream,
+    runtime: Arc<Runtime>,
+    connected: Arc<AtomicUsize>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (tty, leader) = if std::env::args().any(|arg| arg == "--no-pty") {
+        let tty = Tty::from_io(client.try_clone()?, client.try_clone()?, 80, 24)?;
+        (tty, None)
+    } else {
+        let (leader, follower) = open_pty()?;
+        let tty = Tty::new(follower)?;
+        tty.set_size(80, 24)?;
+        (tty, Some(leader))
+    };
+
+    let state = Session {
+        id,
+        presses: 0,
+        connected: connected.clone(),
+    };
+    let mut app = AppWithBackend::<Tokio, TermionBackend>::with_executor(runtime.clone())
+        .build_with(state, update, view)
+        .tty(tty)?;
+    let output = leader
+        .map(|leader| copy_pty(leader, &client, app.handle()))
+        .transpose()?;
+
+    connected.fetch_add(1, Ordering::SeqCst);
+    let exit = runtime.block_on(app.run_async());
+    connected.fetch_sub(1, Ordering::SeqCst);
+    // Closing the tty ends the copy to the client once everything drawn was sent.
+    drop(app);
+    match output {
+        Some(output) => {
+            let _ = output.join();
+            client.shutdown(Shutdown::Both)?;
+        }
+        // The client is disconnected once nothing reads from the socket anymore.
+        None => client.shutdown(Shutdown::Read)?,
+    }
+    exit?;
+    Ok(())
+}
+
+/// Copies the bytes on the leader end of a pseudoterminal to and from `client`, returning the
+/// thread copying to the client.
+fn copy_pty(
+    leader: File,
+    client: &UnixStream,
+    handle: AppHandle<()>,
+) -> io::Result<thread::JoinHandle<()>> {
+    // Client input goes to the application, and quits it once the client disconnects.
+    let (mut from_client, mut to_leader) = (client.try_clone()?, leader.try_clone()?);
+    thread::spawn(move || {
+        let _ = io::copy(&mut from_client, &mut to_leader);
+        let _ = handle.quit();
+    });
+    // Whatever the application draws goes to the client, until the pseudoterminal is closed.
+    // This keeps reading after the client disconnects, as writing to a pseudoterminal fails once
+    // its leader end is closed, and blocks once nothing reads from it.
+    let (mut from_leader, mut to_client) = (leader, client.try_clone()?);
+    Ok(thread::spawn(move || {
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = from_leader.read(&mut buf) {
+            let _ = to_client.write_all(&buf[..n]);
+        }
+    }))
+}
+
+/// Opens a new pseudoterminal, returning its leader and follower ends.
+fn open_pty() -> io::Result<(File, File)> {
+    let (mut leader, mut follower) = (0, 0);
+    // SAFETY: both pointers are valid, and null name, termios and window size are allowed.
+    let result = unsafe {
+        libc::openpty(
+            &mut leader,
+            &mut follower,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `openpty` succeeded, so both file descriptors are open and owned by nothing else.
+    let (leader, follower) =
+        unsafe { (OwnedFd::from_raw_fd(leader), OwnedFd::from_raw_fd(follower)) };
+    Ok((leader.into(), follower.into()))
+}
+
+fn update(state: &mut Session, update: Update<(), Event>) -> Outcome<()> {
+    match update {
+        Update::Terminal(Event::Termion(TermionEvent::Key(Key::Char('q') | Key::Esc))) => {
+            Outcome::quit()
+        }
+        Update::Terminal(Event::Termion(TermionEvent::Key(_))) => {
+            state.presses += 1;
+            Outcome::render()
+        }
+        _ => Outcome::none(),
+    }
+}
+
+fn view(state: &mut Session, frame: &mut Frame) {
+    let block = Block::default()
+        .title(format!("Session {}", state.id))
+        .borders(Borders::ALL);
+    let text = Text::from(vec![
+        format!(
+            "{} sessions connected",
+            state.connected.load(Ordering::SeqCst)
+        )
+        .into(),
+        format!("{} keys pressed in this session", state.presses).into(),
+        "Press q to disconnect".into(),
+    ]);
+    frame.render_widget(text, block.inner(frame.area()));
+    frame.render_widget(block, frame.area());
+}
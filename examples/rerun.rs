@@ -0,0 +1,41 @@
+//! Runs the same application several times, prompting on the command line in between.
+
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    text::Text,
+};
+use ratatui_elm::{AppWithBackend, Outcome, Tokio, Update, backend::CrosstermBackend};
+use std::io::Write;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut app = AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)?;
+    loop {
+        app.run()?;
+        print!("Count is {}. Run again? [y/N] ", app.state());
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if answer.trim() != "y" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn update(state: &mut i64, update: Update<(), Event>) -> Outcome<()> {
+    let Update::Terminal(Event::Key(KeyEvent { code, .. })) = update else {
+        return Outcome::none();
+    };
+    match code {
+        KeyCode::Up | KeyCode::Char('+') => *state += 1,
+        KeyCode::Down | KeyCode::Char('-') => *state -= 1,
+        KeyCode::Char('q') | KeyCode::Esc => return Outcome::quit(),
+        _ => return Outcome::none(),
+    }
+    Outcome::render()
+}
+
+fn view(state: &mut i64, frame: &mut Frame) {
+    frame.render_widget(Text::raw(format!("Count: {state}")), frame.area());
+}
@@ -0,0 +1,49 @@
+//! Runs an application on a tokio runtime that is shared with the rest of the program.
+
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    text::Text,
+};
+use ratatui_elm::{AppWithBackend, Outcome, Tokio, Update, backend::CrosstermBackend};
+use std::{sync::Arc, time::Duration};
+use tokio::runtime::Runtime;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = Arc::new(Runtime::new()?);
+    let mut app = AppWithBackend::<Tokio, CrosstermBackend>::with_executor(runtime.clone())
+        .build(update, view);
+
+    // Some other part of the program, running on the same runtime.
+    let handle = app.handle();
+    runtime.spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            if handle.send(()).is_err() {
+                break;
+            }
+        }
+    });
+
+    runtime.block_on(app.run_async())?;
+    Ok(())
+}
+
+fn update(state: &mut u64, update: Update<(), Event>) -> Outcome<()> {
+    match update {
+        Update::Message(()) => {
+            *state += 1;
+            Outcome::render()
+        }
+        Update::Terminal(Event::Key(KeyEvent {
+            code: KeyCode::Char('q') | KeyCode::Esc,
+            ..
+        })) => Outcome::quit(),
+        Update::Terminal(_) => Outcome::none(),
+    }
+}
+
+fn view(state: &mut u64, frame: &mut Frame) {
+    frame.render_widget(Text::raw(format!("Ticks: {state}")), frame.area());
+}
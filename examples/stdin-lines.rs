@@ -0,0 +1,38 @@
+//! Run with `ls | cargo run --example stdin-lines`.
+
+use futures::StreamExt;
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    text::{Line, Text},
+};
+use ratatui_elm::{
+    AppWithBackend, Task, Tokio, Update, backend::CrosstermBackend, subscription::stdin_lines,
+};
+
+fn main() -> Result<(), ratatui_elm::Error> {
+    AppWithBackend::<Tokio, CrosstermBackend>::new(update, view)?
+        .subscription(stdin_lines::<Tokio>().filter_map(|line| async { line.ok() }))
+        .run()?;
+    Ok(())
+}
+
+fn update(state: &mut Vec<String>, update: Update<String, Event>) -> (Task<String>, bool) {
+    match update {
+        Update::Message(line) => {
+            state.push(line);
+            (Task::None, true)
+        }
+        Update::Terminal(Event::Key(KeyEvent {
+            code: KeyCode::Char('q') | KeyCode::Esc,
+            ..
+        })) => (Task::Quit, false),
+        Update::Terminal(_) => (Task::None, false),
+    }
+}
+
+#[allow(clippy::ptr_arg, reason = "required for type inference to pass")]
+fn view(state: &mut Vec<String>, frame: &mut Frame) {
+    let text = Text::from_iter(state.iter().map(|line| Line::raw(line.as_str())));
+    frame.render_widget(text, frame.area());
+}
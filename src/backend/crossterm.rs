@@ -1,26 +1,239 @@
 use crossterm::event::EventStream;
 use futures::{StreamExt, stream::Fuse};
-use ratatui::crossterm::event::Event;
+use ratatui::{
+    Terminal, Viewport,
+    backend::{Backend, ClearType, WindowSize},
+    buffer::Cell,
+    crossterm::{
+        event::{
+            DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+            EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+            KeyboardEnhancementFlags, MouseButton, MouseEventKind, PopKeyboardEnhancementFlags,
+            PushKeyboardEnhancementFlags,
+        },
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::{Position, Size},
+};
+use std::io;
 
-use crate::backend::New;
+#[cfg(unix)]
+use crate::backend::{Input, Output};
+use crate::{
+    backend::{New, TerminalOptions, cursor::Cursor},
+    input,
+};
 
-pub type CrosstermBackend = ratatui::backend::CrosstermBackend<std::io::Stdout>;
+/// Where the interface is rendered. Only unix can render anywhere but standard output.
+#[cfg(not(unix))]
+type Output = io::Stdout;
+
+/// A crossterm backend that renders to [`TerminalOptions::render_to`].
+///
+/// This wraps ratatui's crossterm backend, which asks standard output where the cursor is
+/// whenever it needs to know, so that the terminal actually being rendered to is asked instead,
+/// and only once, before anything else reads its input.
+pub struct CrosstermBackend {
+    inner: ratatui::backend::CrosstermBackend<Output>,
+    cursor: Cursor,
+}
+
+impl Backend for CrosstermBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(self.cursor.draw(content))
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        self.cursor.append_lines(n, self.inner.size()?);
+        self.inner.append_lines(n)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor.position(self.inner.size()?))
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        let position = position.into();
+        self.cursor.set(position);
+        self.inner.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.inner.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.inner.window_size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.inner)
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_up(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        self.cursor.scroll_region();
+        self.inner.scroll_region_up(region, amount)
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_down(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        self.cursor.scroll_region();
+        self.inner.scroll_region_down(region, amount)
+    }
+}
 
 impl<R> super::Backend<R> for CrosstermBackend {
     type Event = Event;
     type Error = std::io::Error;
     type EventStream = Fuse<EventStream>;
 
-    fn init() -> ratatui::Terminal<Self> {
-        ratatui::init()
+    fn init(options: &TerminalOptions) -> std::io::Result<Terminal<Self>> {
+        super::init(options, setup, |options| {
+            let _ = try_restore(options);
+        })
+    }
+
+    fn restore(options: &TerminalOptions) {
+        if let Err(e) = try_restore(options) {
+            eprintln!("Failed to restore terminal: {e}");
+        }
+    }
+
+    #[cfg(unix)]
+    fn check_tty(_tty: &super::Tty, _options: &TerminalOptions) -> std::io::Result<()> {
+        Err(unsupported_tty())
     }
+}
+
+/// crossterm reads input from a single, process-wide source, which can't be redirected to a
+/// [`Tty`](super::Tty).
+#[cfg(unix)]
+fn unsupported_tty() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "crossterm can only run in the terminal of the process",
+    )
+}
 
-    fn restore() {
-        ratatui::restore();
+/// The keyboard enhancements enabled by [`TerminalOptions::keyboard_enhancement`].
+const KEYBOARD_ENHANCEMENT_FLAGS: KeyboardEnhancementFlags =
+    KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+        .union(KeyboardEnhancementFlags::REPORT_EVENT_TYPES);
+
+/// Set the terminal up as `options` ask, returning a backend that renders to it.
+pub(super) fn setup(options: &TerminalOptions) -> std::io::Result<CrosstermBackend> {
+    #[cfg(unix)]
+    if options.tty.is_some() {
+        return Err(unsupported_tty());
+    }
+    // crossterm enables raw mode on `/dev/tty` when stdin isn't a terminal.
+    enable_raw_mode()?;
+    let mut output = open_output(options)?;
+    if options.viewport == Viewport::Fullscreen {
+        execute!(output, EnterAlternateScreen)?;
+    }
+    if options.mouse {
+        execute!(output, EnableMouseCapture)?;
+    }
+    if options.paste {
+        execute!(output, EnableBracketedPaste)?;
+    }
+    if options.focus {
+        execute!(output, EnableFocusChange)?;
+    }
+    // Terminals that don't support the protocol ignore this.
+    if options.keyboard_enhancement {
+        execute!(
+            output,
+            PushKeyboardEnhancementFlags(KEYBOARD_ENHANCEMENT_FLAGS)
+        )?;
     }
+    let cursor = match options.viewport {
+        // Asked now, as the event stream that would race for the reply doesn't exist yet.
+        Viewport::Inline(_) => Cursor::new(cursor_position(&mut output)?),
+        _ => Cursor::new(Position::ORIGIN),
+    };
+    Ok(CrosstermBackend {
+        inner: ratatui::backend::CrosstermBackend::new(output),
+        cursor,
+    })
+}
+
+#[cfg(unix)]
+fn open_output(options: &TerminalOptions) -> std::io::Result<Output> {
+    Output::open(options)
+}
+
+#[cfg(not(unix))]
+fn open_output(_options: &TerminalOptions) -> std::io::Result<Output> {
+    Ok(io::stdout())
+}
+
+/// Asks the terminal `output` writes to where its cursor is.
+#[cfg(unix)]
+fn cursor_position(output: &mut Output) -> std::io::Result<(u16, u16)> {
+    // The reply arrives on the terminal crossterm reads input from.
+    super::io::cursor_position(output, &Input::terminal()?)
+}
+
+/// Asks the terminal where its cursor is, which crossterm does on standard output.
+#[cfg(not(unix))]
+fn cursor_position(_output: &mut Output) -> std::io::Result<(u16, u16)> {
+    ratatui::crossterm::cursor::position()
+}
+
+fn try_restore(options: &TerminalOptions) -> std::io::Result<()> {
+    // Nothing was set up for another terminal.
+    #[cfg(unix)]
+    if options.tty.is_some() {
+        return Ok(());
+    }
+    // disabling raw mode first is important as it has more side effects than leaving the alternate
+    // screen buffer
+    disable_raw_mode()?;
+    let mut output = open_output(options)?;
+    if options.keyboard_enhancement {
+        execute!(output, PopKeyboardEnhancementFlags)?;
+    }
+    if options.focus {
+        execute!(output, DisableFocusChange)?;
+    }
+    if options.paste {
+        execute!(output, DisableBracketedPaste)?;
+    }
+    if options.mouse {
+        execute!(output, DisableMouseCapture)?;
+    }
+    if options.viewport == Viewport::Fullscreen {
+        execute!(output, LeaveAlternateScreen)?;
+    }
+    Ok(())
 }
 
 impl super::Event for Event {
+    type InputState = ();
+
     fn resize(&self) -> Option<(u16, u16)> {
         if let Event::Resize(w, h) = self {
             Some((*w, *h))
@@ -28,10 +241,332 @@ impl super::Event for Event {
             None
         }
     }
+
+    fn coalesces_with(&self, next: &Self) -> bool {
+        match (self, next) {
+            (Event::Resize(..), Event::Resize(..)) => true,
+            (Event::Mouse(a), Event::Mouse(b)) => {
+                a.modifiers == b.modifiers
+                    && match (a.kind, b.kind) {
+                        (MouseEventKind::Moved, MouseEventKind::Moved) => true,
+                        (MouseEventKind::Drag(a), MouseEventKind::Drag(b)) => a == b,
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+
+    fn to_input(&self) -> Option<input::Event> {
+        to_input(self)
+    }
 }
 
 impl New for Fuse<EventStream> {
-    fn new() -> Self {
-        EventStream::new().fuse()
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn new(options: &TerminalOptions) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        if options.tty.is_some() {
+            return Err(unsupported_tty());
+        }
+        // crossterm only reports errors reading from the terminal through the stream itself.
+        Ok(EventStream::new().fuse())
+    }
+}
+
+/// Convert a crossterm event to its [`input`] form, if it has one.
+fn to_input(event: &Event) -> Option<input::Event> {
+    Some(match event {
+        Event::Key(key) => {
+            let (code, implied) = key_code(key.code)?;
+            input::Event::Key(input::Key {
+                code,
+                modifiers: modifiers(key.modifiers) | implied,
+                kind: key_kind(key.kind),
+            })
+        }
+        Event::Mouse(mouse) => input::Event::Mouse(input::Mouse {
+            kind: match mouse.kind {
+                MouseEventKind::Down(button) => input::MouseKind::Down(mouse_button(button)),
+                MouseEventKind::Up(button) => input::MouseKind::Up(Some(mouse_button(button))),
+                MouseEventKind::Drag(button) => input::MouseKind::Drag(Some(mouse_button(button))),
+                MouseEventKind::Moved => input::MouseKind::Moved,
+                MouseEventKind::ScrollUp => input::MouseKind::ScrollUp,
+                MouseEventKind::ScrollDown => input::MouseKind::ScrollDown,
+                MouseEventKind::ScrollLeft => input::MouseKind::ScrollLeft,
+                MouseEventKind::ScrollRight => input::MouseKind::ScrollRight,
+            },
+            column: mouse.column,
+            row: mouse.row,
+            modifiers: modifiers(mouse.modifiers),
+        }),
+        Event::Paste(text) => input::Event::Paste(input::Paste(text.clone())),
+        Event::FocusGained => input::Event::Focus(input::Focus::Gained),
+        Event::FocusLost => input::Event::Focus(input::Focus::Lost),
+        Event::Resize(width, height) => input::Event::Resize(*width, *height),
+    })
+}
+
+/// Convert a key code, along with the modifiers it implies.
+fn key_code(code: KeyCode) -> Option<(input::KeyCode, input::Modifiers)> {
+    let code = match code {
+        KeyCode::Char(c) => input::KeyCode::Char(c),
+        // Reported for Ctrl+Space.
+        KeyCode::Null => return Some((input::KeyCode::Char(' '), input::Modifiers::CONTROL)),
+        KeyCode::BackTab => return Some((input::KeyCode::BackTab, input::Modifiers::SHIFT)),
+        KeyCode::Enter => input::KeyCode::Enter,
+        KeyCode::Esc => input::KeyCode::Esc,
+        KeyCode::Backspace => input::KeyCode::Backspace,
+        KeyCode::Tab => input::KeyCode::Tab,
+        KeyCode::Delete => input::KeyCode::Delete,
+        KeyCode::Insert => input::KeyCode::Insert,
+        KeyCode::Home => input::KeyCode::Home,
+        KeyCode::End => input::KeyCode::End,
+        KeyCode::PageUp => input::KeyCode::PageUp,
+        KeyCode::PageDown => input::KeyCode::PageDown,
+        KeyCode::Up => input::KeyCode::Up,
+        KeyCode::Down => input::KeyCode::Down,
+        KeyCode::Left => input::KeyCode::Left,
+        KeyCode::Right => input::KeyCode::Right,
+        KeyCode::F(n) => input::KeyCode::F(n),
+        _ => return None,
+    };
+    Some((code, input::Modifiers::NONE))
+}
+
+fn modifiers(modifiers: KeyModifiers) -> input::Modifiers {
+    [
+        (KeyModifiers::SHIFT, input::Modifiers::SHIFT),
+        (KeyModifiers::CONTROL, input::Modifiers::CONTROL),
+        (KeyModifiers::ALT, input::Modifiers::ALT),
+        (KeyModifiers::SUPER, input::Modifiers::SUPER),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| modifiers.contains(*modifier))
+    .fold(input::Modifiers::NONE, |all, (_, modifier)| all | modifier)
+}
+
+fn key_kind(kind: KeyEventKind) -> input::KeyKind {
+    match kind {
+        KeyEventKind::Press => input::KeyKind::Press,
+        KeyEventKind::Repeat => input::KeyKind::Repeat,
+        KeyEventKind::Release => input::KeyKind::Release,
+    }
+}
+
+fn mouse_button(button: MouseButton) -> input::MouseButton {
+    match button {
+        MouseButton::Left => input::MouseButton::Left,
+        MouseButton::Right => input::MouseButton::Right,
+        MouseButton::Middle => input::MouseButton::Middle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::{KeyCode as Code, Modifiers as Mods};
+    use ratatui::crossterm::event::{KeyEvent, MediaKeyCode, MouseEvent};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn mouse(kind: MouseEventKind, modifiers: KeyModifiers) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column: 3,
+            row: 7,
+            modifiers,
+        })
+    }
+
+    #[test]
+    fn keys() {
+        let cases = [
+            (
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                Some((Code::Char('a'), Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Char('A'), KeyModifiers::SHIFT),
+                Some((Code::Char('A'), Mods::SHIFT)),
+            ),
+            (
+                key(
+                    KeyCode::Char('c'),
+                    KeyModifiers::CONTROL | KeyModifiers::ALT,
+                ),
+                Some((Code::Char('c'), Mods::CONTROL | Mods::ALT)),
+            ),
+            (
+                key(KeyCode::Null, KeyModifiers::NONE),
+                Some((Code::Char(' '), Mods::CONTROL)),
+            ),
+            (
+                key(KeyCode::BackTab, KeyModifiers::NONE),
+                Some((Code::BackTab, Mods::SHIFT)),
+            ),
+            (
+                key(KeyCode::BackTab, KeyModifiers::SHIFT),
+                Some((Code::BackTab, Mods::SHIFT)),
+            ),
+            (
+                key(KeyCode::Enter, KeyModifiers::NONE),
+                Some((Code::Enter, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Esc, KeyModifiers::NONE),
+                Some((Code::Esc, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Backspace, KeyModifiers::ALT),
+                Some((Code::Backspace, Mods::ALT)),
+            ),
+            (
+                key(KeyCode::Tab, KeyModifiers::NONE),
+                Some((Code::Tab, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Delete, KeyModifiers::NONE),
+                Some((Code::Delete, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Insert, KeyModifiers::NONE),
+                Some((Code::Insert, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Home, KeyModifiers::CONTROL),
+                Some((Code::Home, Mods::CONTROL)),
+            ),
+            (
+                key(KeyCode::End, KeyModifiers::NONE),
+                Some((Code::End, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::PageUp, KeyModifiers::NONE),
+                Some((Code::PageUp, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::PageDown, KeyModifiers::NONE),
+                Some((Code::PageDown, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Up, KeyModifiers::SHIFT),
+                Some((Code::Up, Mods::SHIFT)),
+            ),
+            (
+                key(KeyCode::Down, KeyModifiers::NONE),
+                Some((Code::Down, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Left, KeyModifiers::NONE),
+                Some((Code::Left, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::Right, KeyModifiers::NONE),
+                Some((Code::Right, Mods::NONE)),
+            ),
+            (
+                key(KeyCode::F(5), KeyModifiers::SUPER),
+                Some((Code::F(5), Mods::SUPER)),
+            ),
+            (key(KeyCode::CapsLock, KeyModifiers::NONE), None),
+            (
+                key(KeyCode::Media(MediaKeyCode::Play), KeyModifiers::NONE),
+                None,
+            ),
+        ];
+        for (event, expected) in cases {
+            let expected =
+                expected.map(|(code, mods)| input::Event::Key(input::Key::new(code, mods)));
+            assert_eq!(to_input(&event), expected, "{event:?}");
+        }
+    }
+
+    #[test]
+    fn key_kinds() {
+        let cases = [
+            (KeyEventKind::Press, input::KeyKind::Press),
+            (KeyEventKind::Repeat, input::KeyKind::Repeat),
+            (KeyEventKind::Release, input::KeyKind::Release),
+        ];
+        for (kind, expected) in cases {
+            let event = Event::Key(KeyEvent::new_with_kind(
+                KeyCode::Char('a'),
+                KeyModifiers::NONE,
+                kind,
+            ));
+            let expected = input::Key {
+                kind: expected,
+                ..input::Key::new(Code::Char('a'), Mods::NONE)
+            };
+            assert_eq!(to_input(&event), Some(input::Event::Key(expected)));
+        }
+    }
+
+    #[test]
+    fn mouse_events() {
+        use input::{MouseButton as Button, MouseKind as Kind};
+        let cases = [
+            (
+                MouseEventKind::Down(MouseButton::Left),
+                Kind::Down(Button::Left),
+            ),
+            (
+                MouseEventKind::Down(MouseButton::Right),
+                Kind::Down(Button::Right),
+            ),
+            (
+                MouseEventKind::Down(MouseButton::Middle),
+                Kind::Down(Button::Middle),
+            ),
+            (
+                MouseEventKind::Up(MouseButton::Left),
+                Kind::Up(Some(Button::Left)),
+            ),
+            (
+                MouseEventKind::Drag(MouseButton::Right),
+                Kind::Drag(Some(Button::Right)),
+            ),
+            (MouseEventKind::Moved, Kind::Moved),
+            (MouseEventKind::ScrollUp, Kind::ScrollUp),
+            (MouseEventKind::ScrollDown, Kind::ScrollDown),
+            (MouseEventKind::ScrollLeft, Kind::ScrollLeft),
+            (MouseEventKind::ScrollRight, Kind::ScrollRight),
+        ];
+        for (kind, expected) in cases {
+            let event = mouse(kind, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+            let expected = input::Mouse {
+                kind: expected,
+                column: 3,
+                row: 7,
+                modifiers: Mods::CONTROL | Mods::SHIFT,
+            };
+            assert_eq!(
+                to_input(&event),
+                Some(input::Event::Mouse(expected)),
+                "{event:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_events() {
+        let cases = [
+            (
+                Event::Paste("text".into()),
+                input::Event::Paste(input::Paste("text".into())),
+            ),
+            (
+                Event::FocusGained,
+                input::Event::Focus(input::Focus::Gained),
+            ),
+            (Event::FocusLost, input::Event::Focus(input::Focus::Lost)),
+            (Event::Resize(80, 24), input::Event::Resize(80, 24)),
+        ];
+        for (event, expected) in cases {
+            assert_eq!(to_input(&event), Some(expected), "{event:?}");
+        }
     }
 }
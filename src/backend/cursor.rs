@@ -0,0 +1,59 @@
+use ratatui::{
+    buffer::Cell,
+    layout::{Position, Size},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Where the cursor of a terminal is, followed through what a backend writes to it.
+///
+/// The terminal itself can only be asked by reading its reply from the same input the backend's
+/// event stream reads, which would race that stream for the reply. It is only asked once, while
+/// the terminal is set up and before the stream exists, and the cursor is followed from there.
+pub(crate) struct Cursor(Position);
+
+impl Cursor {
+    /// Follow the cursor from `position`.
+    pub(crate) fn new(position: impl Into<Position>) -> Self {
+        Self(position.into())
+    }
+
+    /// Where the cursor is, kept within a terminal of `size`, whose cursor a resize may have moved.
+    pub(crate) fn position(&self, size: Size) -> Position {
+        Position {
+            x: self.0.x.min(size.width.saturating_sub(1)),
+            y: self.0.y.min(size.height.saturating_sub(1)),
+        }
+    }
+
+    /// Follow the cursor as `content` is drawn: it is left after the last cell drawn.
+    pub(crate) fn draw<'a>(
+        &mut self,
+        content: impl Iterator<Item = (u16, u16, &'a Cell)>,
+    ) -> impl Iterator<Item = (u16, u16, &'a Cell)> {
+        content.inspect(|(x, y, cell)| {
+            let width = u16::try_from(cell.symbol().width()).unwrap_or(u16::MAX);
+            self.0 = Position::new(x.saturating_add(width), *y);
+        })
+    }
+
+    pub(crate) fn set(&mut self, position: Position) {
+        self.0 = position;
+    }
+
+    /// Follow the cursor down `n` lines, scrolling a terminal of `size` once it reaches the bottom.
+    ///
+    /// Raw mode leaves the column as it is.
+    pub(crate) fn append_lines(&mut self, n: u16, size: Size) {
+        self.0.y = self
+            .0
+            .y
+            .saturating_add(n)
+            .min(size.height.saturating_sub(1));
+    }
+
+    /// Follow the cursor home, where resetting the scrolling region moves it.
+    #[cfg(feature = "scrolling-regions")]
+    pub(crate) fn scroll_region(&mut self) {
+        self.0 = Position::ORIGIN;
+    }
+}
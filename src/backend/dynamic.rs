@@ -0,0 +1,446 @@
+use std::{error::Error, fmt, io, marker::PhantomData, str::FromStr};
+
+use futures::{
+    Stream, StreamExt,
+    future::ready,
+    stream::{Fuse, FusedStream, LocalBoxStream},
+};
+use ratatui::{
+    Terminal,
+    backend::{Backend, ClearType, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size},
+};
+
+use super::{New, TerminalOptions};
+use crate::input;
+
+#[cfg(feature = "crossterm")]
+use super::CrosstermBackend;
+#[cfg(feature = "termion")]
+use super::TermionBackend;
+#[cfg(feature = "termwiz")]
+use super::TermwizBackend;
+
+/// Which backend a [`DynBackend`] uses, set with [`App::backend`](crate::App::backend).
+///
+/// Only the backends whose cargo feature is enabled are available. This can be parsed from a
+/// backend's name, such as `"termwiz"`, to choose it from an environment variable or a command
+/// line flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    #[cfg(feature = "crossterm")]
+    Crossterm,
+    #[cfg(feature = "termion")]
+    Termion,
+    #[cfg(feature = "termwiz")]
+    Termwiz,
+}
+
+impl BackendKind {
+    /// The name of the backend, as it is parsed.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "crossterm")]
+            Self::Crossterm => "crossterm",
+            #[cfg(feature = "termion")]
+            Self::Termion => "termion",
+            #[cfg(feature = "termwiz")]
+            Self::Termwiz => "termwiz",
+        }
+    }
+}
+
+/// crossterm if it is enabled, then termwiz, then termion.
+impl Default for BackendKind {
+    fn default() -> Self {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "crossterm")] {
+                Self::Crossterm
+            } else if #[cfg(feature = "termwiz")] {
+                Self::Termwiz
+            } else {
+                Self::Termion
+            }
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = ParseBackendKindError;
+
+    /// Parse the name of an enabled backend, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "crossterm")]
+            "crossterm" => Ok(Self::Crossterm),
+            #[cfg(feature = "termion")]
+            "termion" => Ok(Self::Termion),
+            #[cfg(feature = "termwiz")]
+            "termwiz" => Ok(Self::Termwiz),
+            _ => Err(ParseBackendKindError(s.to_owned())),
+        }
+    }
+}
+
+/// The error returned when parsing a [`BackendKind`] from anything but the name of an enabled
+/// backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBackendKindError(String);
+
+impl fmt::Display for ParseBackendKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown or disabled backend `{}`", self.0)
+    }
+}
+
+impl Error for ParseBackendKindError {}
+
+/// A backend chosen when the application starts running, among those whose cargo feature is
+/// enabled.
+///
+/// Its events are [`input::Event`]s, whichever backend is chosen, so that update functions are
+/// written once. Events that have no [`input`] form are dropped.
+///
+/// The backend is chosen with [`App::backend`](crate::App::backend), and defaults to
+/// [`BackendKind::default`].
+#[allow(
+    clippy::large_enum_variant,
+    reason = "an application only has one backend, which is never moved while it runs"
+)]
+pub enum DynBackend {
+    #[cfg(feature = "crossterm")]
+    Crossterm(CrosstermBackend),
+    #[cfg(feature = "termion")]
+    Termion(TermionBackend),
+    #[cfg(feature = "termwiz")]
+    Termwiz(TermwizBackend),
+}
+
+/// Call the same method on whichever backend is in use.
+macro_rules! dispatch {
+    ($self:expr, $backend:ident => $call:expr) => {
+        match $self {
+            #[cfg(feature = "crossterm")]
+            DynBackend::Crossterm($backend) => $call,
+            #[cfg(feature = "termion")]
+            DynBackend::Termion($backend) => $call,
+            #[cfg(feature = "termwiz")]
+            DynBackend::Termwiz($backend) => $call,
+        }
+    };
+}
+
+impl DynBackend {
+    /// Which backend is in use.
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            #[cfg(feature = "crossterm")]
+            Self::Crossterm(_) => BackendKind::Crossterm,
+            #[cfg(feature = "termion")]
+            Self::Termion(_) => BackendKind::Termion,
+            #[cfg(feature = "termwiz")]
+            Self::Termwiz(_) => BackendKind::Termwiz,
+        }
+    }
+
+    /// Set the terminal up as `options` ask with the backend they choose.
+    fn setup(options: &TerminalOptions) -> io::Result<Self> {
+        Ok(match options.backend.unwrap_or_default() {
+            #[cfg(feature = "crossterm")]
+            BackendKind::Crossterm => Self::Crossterm(super::crossterm::setup(options)?),
+            #[cfg(feature = "termion")]
+            BackendKind::Termion => Self::Termion(super::termion::setup(options)?),
+            #[cfg(feature = "termwiz")]
+            BackendKind::Termwiz => Self::Termwiz(super::termwiz::setup(options)?),
+        })
+    }
+}
+
+impl Backend for DynBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        dispatch!(self, backend => backend.draw(content))
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        dispatch!(self, backend => backend.append_lines(n))
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        dispatch!(self, backend => backend.hide_cursor())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        dispatch!(self, backend => backend.show_cursor())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        dispatch!(self, backend => backend.get_cursor_position())
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        dispatch!(self, backend => backend.set_cursor_position(position))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        dispatch!(self, backend => backend.clear())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        dispatch!(self, backend => backend.clear_region(clear_type))
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        dispatch!(self, backend => backend.size())
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        dispatch!(self, backend => backend.window_size())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        dispatch!(self, backend => Backend::flush(backend))
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_up(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        dispatch!(self, backend => backend.scroll_region_up(region, amount))
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_down(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        dispatch!(self, backend => backend.scroll_region_down(region, amount))
+    }
+}
+
+/// An asynchronous stream of events from whichever backend a [`DynBackend`] uses.
+pub struct DynEventStream<R> {
+    inner: Fuse<LocalBoxStream<'static, io::Result<input::Event>>>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> DynEventStream<R> {
+    /// Convert the events of a backend's stream to their [`input`] form.
+    fn convert<E, X>(events: impl Stream<Item = Result<E, X>> + 'static) -> Self
+    where
+        E: super::Event + 'static,
+        X: Into<Box<dyn Error + Send + Sync>> + 'static,
+    {
+        let mut state = E::InputState::default();
+        let inner = events.filter_map(move |item| {
+            ready(match item {
+                Ok(event) => event.to_input_with(&mut state).map(Ok),
+                Err(e) => Some(Err(io::Error::other(e))),
+            })
+        });
+        Self {
+            inner: inner.boxed_local().fuse(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> Stream for DynEventStream<R> {
+    type Item = io::Result<input::Event>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<R> FusedStream for DynEventStream<R> {
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+/// Implement [`super::Backend`] for [`DynBackend`] on each enabled runtime.
+///
+/// The other backends only support runtimes whose channels can be sent to their input threads.
+/// Every concrete runtime can, but there is no way to require that of a generic runtime for only
+/// the backends that are enabled.
+macro_rules! impl_backend {
+    ($($feature:literal => $runtime:ty),* $(,)?) => {$(
+        #[cfg(feature = $feature)]
+        impl super::Backend<$runtime> for DynBackend {
+            type Event = input::Event;
+            type Error = io::Error;
+            type EventStream = DynEventStream<$runtime>;
+
+            fn init(options: &TerminalOptions) -> io::Result<Terminal<Self>> {
+                super::init(
+                    options,
+                    Self::setup,
+                    <Self as super::Backend<$runtime>>::restore,
+                )
+            }
+
+            fn restore(options: &TerminalOptions) {
+                match options.backend.unwrap_or_default() {
+                    #[cfg(feature = "crossterm")]
+                    BackendKind::Crossterm => {
+                        <CrosstermBackend as super::Backend<$runtime>>::restore(options)
+                    }
+                    #[cfg(feature = "termion")]
+                    BackendKind::Termion => {
+                        <TermionBackend as super::Backend<$runtime>>::restore(options)
+                    }
+                    #[cfg(feature = "termwiz")]
+                    BackendKind::Termwiz => {
+                        <TermwizBackend as super::Backend<$runtime>>::restore(options)
+                    }
+                }
+            }
+
+            #[cfg(unix)]
+            fn check_tty(tty: &super::Tty, options: &TerminalOptions) -> io::Result<()> {
+                match options.backend.unwrap_or_default() {
+                    #[cfg(feature = "crossterm")]
+                    BackendKind::Crossterm => {
+                        <CrosstermBackend as super::Backend<$runtime>>::check_tty(tty, options)
+                    }
+                    #[cfg(feature = "termion")]
+                    BackendKind::Termion => {
+                        <TermionBackend as super::Backend<$runtime>>::check_tty(tty, options)
+                    }
+                    #[cfg(feature = "termwiz")]
+                    BackendKind::Termwiz => {
+                        <TermwizBackend as super::Backend<$runtime>>::check_tty(tty, options)
+                    }
+                }
+            }
+
+            fn handle_resize(&mut self, width: u16, height: u16) {
+                dispatch!(
+                    self,
+                    backend => super::Backend::<$runtime>::handle_resize(backend, width, height)
+                )
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl New for DynEventStream<$runtime> {
+            fn new(options: &TerminalOptions) -> io::Result<Self> {
+                Ok(match options.backend.unwrap_or_default() {
+                    #[cfg(feature = "crossterm")]
+                    BackendKind::Crossterm => Self::convert(
+                        <CrosstermBackend as super::Backend<$runtime>>::EventStream::new(options)?,
+                    ),
+                    #[cfg(feature = "termion")]
+                    BackendKind::Termion => Self::convert(
+                        <TermionBackend as super::Backend<$runtime>>::EventStream::new(options)?,
+                    ),
+                    #[cfg(feature = "termwiz")]
+                    BackendKind::Termwiz => Self::convert(
+                        <TermwizBackend as super::Backend<$runtime>>::EventStream::new(options)?,
+                    ),
+                })
+            }
+        }
+    )*};
+}
+
+impl_backend! {
+    "tokio" => crate::Tokio,
+    "smol" => crate::Smol,
+    "futures" => crate::Futures,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_kinds_parse_from_enabled_backend_names() {
+        let cases = [
+            #[cfg(feature = "crossterm")]
+            ("crossterm", Some(BackendKind::Crossterm)),
+            #[cfg(feature = "crossterm")]
+            ("CrossTerm", Some(BackendKind::Crossterm)),
+            #[cfg(not(feature = "crossterm"))]
+            ("crossterm", None),
+            #[cfg(feature = "termion")]
+            ("termion", Some(BackendKind::Termion)),
+            #[cfg(feature = "termion")]
+            ("TERMION", Some(BackendKind::Termion)),
+            #[cfg(not(feature = "termion"))]
+            ("termion", None),
+            #[cfg(feature = "termwiz")]
+            ("termwiz", Some(BackendKind::Termwiz)),
+            #[cfg(feature = "termwiz")]
+            ("Termwiz", Some(BackendKind::Termwiz)),
+            #[cfg(not(feature = "termwiz"))]
+            ("termwiz", None),
+            ("", None),
+            ("ratatui", None),
+            (" termion", None),
+            ("crossterm\n", None),
+        ];
+        for (name, kind) in cases {
+            let expected = kind.ok_or_else(|| ParseBackendKindError(name.to_owned()));
+            assert_eq!(name.parse::<BackendKind>(), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn backend_kinds_display_as_the_name_they_parse_from() {
+        let kinds = [
+            #[cfg(feature = "crossterm")]
+            BackendKind::Crossterm,
+            #[cfg(feature = "termion")]
+            BackendKind::Termion,
+            #[cfg(feature = "termwiz")]
+            BackendKind::Termwiz,
+        ];
+        for kind in kinds {
+            assert_eq!(kind.to_string(), kind.name());
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+
+    #[test]
+    fn parse_errors_name_what_was_parsed() {
+        let error = "ratatui".parse::<BackendKind>().unwrap_err();
+        assert_eq!(error.to_string(), "unknown or disabled backend `ratatui`");
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "crossterm", feature = "tokio"))]
+    fn crossterm_rejects_a_tty_before_setting_anything_up() {
+        let options = TerminalOptions {
+            backend: Some(BackendKind::Crossterm),
+            tty: Some(crate::backend::Tty::from_io(io::empty(), io::sink(), 80, 24).unwrap()),
+            ..Default::default()
+        };
+        match <DynBackend as crate::backend::Backend<crate::Tokio>>::init(&options) {
+            Ok(_) => panic!("crossterm shouldn't run in a tty"),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+        }
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "termwiz", feature = "tokio"))]
+    fn termwiz_rejects_a_reader_and_writer_before_running() {
+        let tty = crate::backend::Tty::from_io(io::empty(), io::sink(), 80, 24).unwrap();
+        let options = TerminalOptions {
+            backend: Some(BackendKind::Termwiz),
+            ..Default::default()
+        };
+        let error =
+            <DynBackend as crate::backend::Backend<crate::Tokio>>::check_tty(&tty, &options)
+                .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
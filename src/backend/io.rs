@@ -0,0 +1,465 @@
+use futures::channel::mpsc::UnboundedSender;
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+use futures::channel::mpsc::{self, UnboundedReceiver};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, PipeReader, PipeWriter, Read, Result, Stderr, Stdin, Stdout, Write},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use super::TerminalOptions;
+
+/// Opens the controlling terminal of the process.
+pub(crate) fn open_tty() -> Result<File> {
+    OpenOptions::new().read(true).write(true).open("/dev/tty")
+}
+
+/// Asks the terminal `output` writes to where its cursor is, reading the reply from `input`, as a
+/// zero-based `(column, row)`.
+///
+/// The terminal must be in raw mode, and nothing else may be reading its input.
+pub(crate) fn cursor_position(output: &mut impl Write, input: &impl AsFd) -> Result<(u16, u16)> {
+    output.write_all(b"\x1b[6n")?;
+    output.flush()?;
+    // Read directly rather than through `input`, whose buffer (as for `Stdin`) could keep part of
+    // the reply from `poll`.
+    let mut input = File::from(input.as_fd().try_clone_to_owned()?);
+    // The reply is `ESC [ row ; column R`.
+    let mut reply = Vec::new();
+    while reply.last() != Some(&b'R') {
+        let mut fd = libc::pollfd {
+            fd: input.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: `fd` is a single valid `pollfd`.
+        if unsafe { libc::poll(&mut fd, 1, 1000) } <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the terminal didn't report the cursor position",
+            ));
+        }
+        let mut byte = 0;
+        input.read_exact(std::slice::from_mut(&mut byte))?;
+        reply.push(byte);
+    }
+    let reply = String::from_utf8_lossy(&reply);
+    reply
+        .rsplit_once("\x1b[")
+        .and_then(|(_, reply)| reply.strip_suffix('R')?.split_once(';'))
+        .and_then(|(row, column)| Some((column.parse::<u16>().ok()?, row.parse::<u16>().ok()?)))
+        .map(|(column, row)| (column.saturating_sub(1), row.saturating_sub(1)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid cursor position report"))
+}
+
+/// A terminal to run an application in instead of the terminal of the process, set with
+/// [`App::tty`](crate::App::tty).
+///
+/// This is either a terminal device, usually the follower end of a pseudoterminal whose leader end
+/// is driven by something else (a terminal multiplexer pane, or a remote user connected over a
+/// socket), or [any reader and writer](Tty::from_io), such as the socket itself. Each application
+/// running in its own `Tty` is independent of the others, so several can run at once, each on its
+/// own thread.
+///
+/// Resizes are reported to the applications running in a `Tty` when its size is set with
+/// [`Tty::set_size`]. A terminal device that is the controlling terminal of the process is also
+/// resized by the kernel, which reports it with `SIGWINCH`.
+///
+/// Not every backend can run in every kind of `Tty`:
+///
+/// | Backend   | Terminal device | Reader and writer |
+/// |-----------|-----------------|-------------------|
+/// | crossterm | No              | No                |
+/// | termion   | Yes             | Yes               |
+/// | termwiz   | Yes             | No                |
+///
+/// crossterm reads input from a single source for the whole process, which can't be redirected.
+/// termwiz sets the mode of the terminal device itself, which a reader and writer doesn't have.
+/// Initializing a backend in a `Tty` it can't run in fails with
+/// [`ErrorKind::Unsupported`](io::ErrorKind::Unsupported), before anything is set up.
+#[derive(Clone)]
+pub struct Tty(Arc<TtyInner>);
+
+struct TtyInner {
+    kind: TtyKind,
+    /// Told about every resize made with [`Tty::set_size`].
+    resized: Mutex<Vec<UnboundedSender<(u16, u16)>>>,
+}
+
+enum TtyKind {
+    Device(File),
+    /// A reader and writer, copied to and from these pipes by threads of their own so that
+    /// backends can poll them like a terminal device.
+    Io {
+        input: PipeReader,
+        output: PipeWriter,
+        size: Mutex<(u16, u16)>,
+    },
+}
+
+impl Tty {
+    /// Use an open terminal device.
+    ///
+    /// Fails if `file` isn't a terminal.
+    pub fn new(file: File) -> Result<Self> {
+        if !file.is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the file isn't a terminal",
+            ));
+        }
+        Ok(Self::with_kind(TtyKind::Device(file)))
+    }
+
+    /// Open the terminal device at `path`, such as `/dev/pts/3`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
+    /// Use a terminal that sends its input to `reader` and displays what is written to `writer`,
+    /// and is `width` columns by `height` rows until [set](Tty::set_size) otherwise.
+    ///
+    /// There is no terminal mode to set, so the other end is expected to pass keys through as
+    /// they are typed, without echoing them, as a terminal in raw mode would. Input ends once
+    /// `reader` reaches its end, which the application sees as its
+    /// [input closing](crate::Exit::InputClosed).
+    ///
+    /// `reader` and `writer` are moved to threads of their own. The thread reading `reader` ends
+    /// once `reader` does, or once it reads anything after every handle to this `Tty` is dropped.
+    /// The thread writing to `writer` ends once every handle to this `Tty` is dropped and
+    /// everything written was sent.
+    pub fn from_io(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        width: u16,
+        height: u16,
+    ) -> Result<Self> {
+        let (input, to_input) = io::pipe()?;
+        let (from_output, output) = io::pipe()?;
+        thread::Builder::new()
+            .name("ratatui-elm tty input".into())
+            .spawn(move || {
+                let _ = forward(reader, to_input);
+            })?;
+        thread::Builder::new()
+            .name("ratatui-elm tty output".into())
+            .spawn(move || {
+                let _ = forward(from_output, writer);
+            })?;
+        Ok(Self::with_kind(TtyKind::Io {
+            input,
+            output,
+            size: Mutex::new((width, height)),
+        }))
+    }
+
+    fn with_kind(kind: TtyKind) -> Self {
+        Self(Arc::new(TtyInner {
+            kind,
+            resized: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// The size of the terminal, in columns and rows.
+    pub fn size(&self) -> Result<(u16, u16)> {
+        match &self.0.kind {
+            TtyKind::Device(file) => {
+                let mut size = libc::winsize {
+                    ws_row: 0,
+                    ws_col: 0,
+                    ws_xpixel: 0,
+                    ws_ypixel: 0,
+                };
+                // SAFETY: the file descriptor is valid and `size` is a valid `winsize`.
+                if unsafe { libc::ioctl(file.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok((size.ws_col, size.ws_row))
+            }
+            TtyKind::Io { size, .. } => Ok(*size.lock().unwrap_or_else(PoisonError::into_inner)),
+        }
+    }
+
+    /// Set the size of the terminal, in columns and rows, and report the resize to the
+    /// applications running in it.
+    pub fn set_size(&self, width: u16, height: u16) -> Result<()> {
+        match &self.0.kind {
+            TtyKind::Device(file) => {
+                let size = libc::winsize {
+                    ws_row: height,
+                    ws_col: width,
+                    ws_xpixel: 0,
+                    ws_ypixel: 0,
+                };
+                // SAFETY: the file descriptor is valid and `size` is a valid `winsize`.
+                if unsafe { libc::ioctl(file.as_raw_fd(), libc::TIOCSWINSZ, &size) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            TtyKind::Io { size, .. } => {
+                *size.lock().unwrap_or_else(PoisonError::into_inner) = (width, height);
+            }
+        }
+        self.0
+            .resized
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|tx| tx.unbounded_send((width, height)).is_ok());
+        Ok(())
+    }
+
+    /// The terminal device, unless this is a reader and writer.
+    #[cfg(any(feature = "termion", feature = "termwiz"))]
+    pub(crate) fn device(&self) -> Option<&File> {
+        match &self.0.kind {
+            TtyKind::Device(file) => Some(file),
+            TtyKind::Io { .. } => None,
+        }
+    }
+
+    /// A new handle to read the terminal's input from.
+    pub(crate) fn input(&self) -> Result<File> {
+        match &self.0.kind {
+            TtyKind::Device(file) => file.try_clone(),
+            TtyKind::Io { input, .. } => Ok(OwnedFd::from(input.try_clone()?).into()),
+        }
+    }
+
+    /// A new handle to write to the terminal.
+    pub(crate) fn output(&self) -> Result<File> {
+        match &self.0.kind {
+            TtyKind::Device(file) => file.try_clone(),
+            TtyKind::Io { output, .. } => Ok(OwnedFd::from(output.try_clone()?).into()),
+        }
+    }
+
+    /// Every size set with [`Tty::set_size`] from now on.
+    #[cfg(any(feature = "termion", feature = "termwiz"))]
+    pub(crate) fn resizes(&self) -> UnboundedReceiver<(u16, u16)> {
+        let (tx, rx) = mpsc::unbounded();
+        self.0
+            .resized
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(tx);
+        rx
+    }
+}
+
+/// Copy everything read from `from` to `to`, flushing it as soon as it is read so that nothing is
+/// held back.
+///
+/// This isn't [`io::copy`], which splices sockets into pipes on Linux in a way that can leave a
+/// pipe reported as readable before a read can return anything.
+fn forward(mut from: impl Read, mut to: impl Write) -> Result<()> {
+    let mut buf = [0; 4096];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                to.write_all(&buf[..n])?;
+                to.flush()?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Two `Tty`s are equal if they are handles to the same terminal.
+impl PartialEq for Tty {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Tty {}
+
+impl fmt::Debug for Tty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0.kind {
+            TtyKind::Device(file) => f.debug_tuple("Tty").field(&file.as_raw_fd()).finish(),
+            TtyKind::Io { size, .. } => f
+                .debug_struct("Tty")
+                .field(
+                    "size",
+                    &*size.lock().unwrap_or_else(PoisonError::into_inner),
+                )
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Which of the terminals of the process the interface is rendered to, set with
+/// [`App::render_to`](crate::App::render_to).
+///
+/// Rendering anywhere but standard output leaves it free for the program's own output, such as
+/// the value an application [exits](crate::Exit::Value) with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// Standard output if it is a terminal, or the controlling terminal otherwise. See
+    /// [`Output::terminal`].
+    #[default]
+    Auto,
+    /// Standard output.
+    Stdout,
+    /// Standard error.
+    Stderr,
+    /// The controlling terminal (`/dev/tty`).
+    Tty,
+}
+
+/// Where a backend writes the rendered interface.
+pub enum Output {
+    /// The standard output of the process.
+    Stdout(Stdout),
+    /// The standard error of the process.
+    Stderr(Stderr),
+    /// The controlling terminal of the process (`/dev/tty`), or a [`Tty`].
+    Tty(File),
+}
+
+impl Output {
+    /// Standard output if it is a terminal, or the controlling terminal otherwise.
+    ///
+    /// This allows the interface to be rendered even when standard output is redirected, as in
+    /// `result=$(my-picker)`.
+    pub fn terminal() -> Result<Self> {
+        let stdout = std::io::stdout();
+        if stdout.is_terminal() {
+            Ok(Self::Stdout(stdout))
+        } else {
+            open_tty().map(Self::Tty)
+        }
+    }
+
+    /// The output `options` ask for: their [`Tty`] if any, or their [`RenderTarget`] otherwise.
+    pub fn open(options: &TerminalOptions) -> Result<Self> {
+        if let Some(tty) = &options.tty {
+            return tty.output().map(Self::Tty);
+        }
+        match options.render_to {
+            RenderTarget::Auto => Self::terminal(),
+            RenderTarget::Stdout => Ok(Self::Stdout(std::io::stdout())),
+            RenderTarget::Stderr => Ok(Self::Stderr(std::io::stderr())),
+            RenderTarget::Tty => open_tty().map(Self::Tty),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+            Self::Tty(tty) => tty.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+            Self::Tty(tty) => tty.flush(),
+        }
+    }
+}
+
+impl AsFd for Output {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        match self {
+            Self::Stdout(stdout) => stdout.as_fd(),
+            Self::Stderr(stderr) => stderr.as_fd(),
+            Self::Tty(tty) => tty.as_fd(),
+        }
+    }
+}
+
+/// Where a backend reads terminal input from.
+pub enum Input {
+    /// The standard input of the process.
+    Stdin(Stdin),
+    /// The controlling terminal of the process (`/dev/tty`), or a [`Tty`].
+    Tty(File),
+}
+
+impl Input {
+    /// Standard input if it is a terminal, or the controlling terminal otherwise.
+    ///
+    /// This leaves standard input free to be read as data, as in `find . | my-picker`. See
+    /// [`subscription::stdin_lines`](crate::subscription::stdin_lines).
+    pub fn terminal() -> Result<Self> {
+        let stdin = std::io::stdin();
+        if stdin.is_terminal() {
+            Ok(Self::Stdin(stdin))
+        } else {
+            open_tty().map(Self::Tty)
+        }
+    }
+
+    /// `tty` if given, or [`Input::terminal`] otherwise.
+    pub fn open(tty: Option<&Tty>) -> Result<Self> {
+        match tty {
+            Some(tty) => tty.input().map(Self::Tty),
+            None => Self::terminal(),
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Self::Stdin(stdin) => stdin.read(buf),
+            Self::Tty(tty) => tty.read(buf),
+        }
+    }
+}
+
+impl AsFd for Input {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        match self {
+            Self::Stdin(stdin) => stdin.as_fd(),
+            Self::Tty(tty) => tty.as_fd(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn io_ttys_copy_input_and_output() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let tty = Tty::from_io(server.try_clone().unwrap(), server, 80, 24).unwrap();
+
+        client.write_all(b"keys").unwrap();
+        let mut input = [0; 4];
+        tty.input().unwrap().read_exact(&mut input).unwrap();
+        assert_eq!(&input, b"keys");
+
+        tty.output().unwrap().write_all(b"frame").unwrap();
+        let mut output = [0; 5];
+        client.read_exact(&mut output).unwrap();
+        assert_eq!(&output, b"frame");
+    }
+
+    #[test]
+    #[cfg(any(feature = "termion", feature = "termwiz"))]
+    fn set_size_is_reported_to_the_tty_only() {
+        let tty = Tty::from_io(io::empty(), io::sink(), 80, 24).unwrap();
+        let other = Tty::from_io(io::empty(), io::sink(), 80, 24).unwrap();
+        let (mut resizes, mut other_resizes) = (tty.resizes(), other.resizes());
+
+        tty.set_size(100, 30).unwrap();
+        assert_eq!(tty.size().unwrap(), (100, 30));
+        assert_eq!(other.size().unwrap(), (80, 24));
+        assert_eq!(resizes.try_recv(), Ok((100, 30)));
+        assert!(other_resizes.try_recv().is_err());
+    }
+}
@@ -11,35 +11,188 @@ pub use termion::TermionBackend;
 
 #[cfg(feature = "termwiz")]
 mod termwiz;
+#[cfg(feature = "termwiz")]
+pub use termwiz::TermwizBackend;
+
+#[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))]
+mod dynamic;
+#[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))]
+pub use dynamic::{BackendKind, DynBackend, DynEventStream, ParseBackendKindError};
+
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+mod cursor;
 
-use ratatui::Terminal;
+#[cfg(unix)]
+mod io;
+#[cfg(unix)]
+pub use io::{Input, Output, RenderTarget, Tty};
+
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+use futures::{
+    StreamExt,
+    stream::{BoxStream, SelectAll},
+};
+use ratatui::{Terminal, Viewport};
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+use std::task::{Context, Poll};
 
 /// Some extra functionality that a backend must have for ratatui-elm to work.
 pub trait Backend<R>: ratatui::backend::Backend + Sized {
     /// The type of event that the backend produces.
     type Event: Event;
     /// The type of error that the backend produces.
-    type Error: std::error::Error;
+    type Error: std::error::Error + Send + Sync + 'static;
     /// An asynchronous stream of events.
     type EventStream: FusedStream + Stream<Item = Result<Self::Event, Self::Error>> + New + Unpin;
 
     /// Initialize the backend.
-    fn init() -> Terminal<Self>;
-    /// Restore the terminal to its original state.
-    fn restore();
+    ///
+    /// The alternate screen is only entered for [`Viewport::Fullscreen`]. Inline and fixed
+    /// viewports are drawn on the main screen, alongside the rest of the terminal's contents.
+    ///
+    /// If this fails, whatever it had already changed about the terminal is undone.
+    fn init(options: &TerminalOptions) -> std::io::Result<Terminal<Self>>;
+    /// Restore the terminal to its original state, undoing what [`Backend::init`] enabled with
+    /// the same `options`.
+    ///
+    /// This may be called from a panic hook while the terminal returned by [`Backend::init`] is
+    /// still alive, so it must not rely on that terminal being dropped.
+    fn restore(options: &TerminalOptions);
+    /// Check that the backend can run in `tty` with `options`, so that
+    /// [`App::tty`](crate::App::tty) rejects a `tty` it can't run in before the application
+    /// runs.
+    #[cfg(unix)]
+    fn check_tty(_tty: &Tty, _options: &TerminalOptions) -> std::io::Result<()> {
+        Ok(())
+    }
 
     fn handle_resize(&mut self, _width: u16, _height: u16) {}
 }
 
+/// How the terminal is set up while an application runs, set with
+/// [`App::terminal_options`](crate::App::terminal_options).
+///
+/// Everything is disabled by default. Not every backend can report every kind of event: options a
+/// backend can't handle are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalOptions {
+    /// The area of the terminal the interface is rendered to. See
+    /// [`App::viewport`](crate::App::viewport).
+    pub viewport: Viewport,
+    /// Which terminal of the process the interface is rendered to. See
+    /// [`App::render_to`](crate::App::render_to).
+    #[cfg(unix)]
+    pub render_to: RenderTarget,
+    /// Report mouse clicks, drags and scrolls.
+    pub mouse: bool,
+    /// Report pasted text as a single event instead of as keypresses.
+    ///
+    /// Ignored by termion.
+    pub paste: bool,
+    /// Report when the terminal gains or loses focus.
+    ///
+    /// Ignored by termion and termwiz.
+    pub focus: bool,
+    /// Use the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/),
+    /// where supported, to report keys that are otherwise ambiguous (such as <kbd>Esc</kbd> and
+    /// <kbd>Alt</kbd> combinations) along with key repeats and releases.
+    ///
+    /// Ignored by termion and termwiz. termwiz always asks for unambiguous keys through xterm's
+    /// `modifyOtherKeys` instead.
+    pub keyboard_enhancement: bool,
+    /// The terminal to run in, instead of the terminal of the process. See
+    /// [`App::tty`](crate::App::tty).
+    ///
+    /// Not supported by crossterm, and only supported by termwiz for terminal devices. See
+    /// [`Tty`] for which backend supports which kind of `Tty`.
+    #[cfg(unix)]
+    pub tty: Option<Tty>,
+    /// The backend a [`DynBackend`] uses, or [`BackendKind::default`] if unset. See
+    /// [`App::backend`](crate::App::backend).
+    ///
+    /// Ignored by every other backend.
+    #[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))]
+    pub backend: Option<BackendKind>,
+}
+
+/// Set the terminal up as `options` ask with `setup`, and wrap the backend in a terminal.
+///
+/// If either fails, `restore` undoes whatever part of the terminal was set up before the error.
+fn init<B: ratatui::backend::Backend>(
+    options: &TerminalOptions,
+    setup: impl FnOnce(&TerminalOptions) -> std::io::Result<B>,
+    restore: impl FnOnce(&TerminalOptions),
+) -> std::io::Result<Terminal<B>> {
+    setup(options)
+        .and_then(|backend| {
+            Terminal::with_options(
+                backend,
+                ratatui::TerminalOptions {
+                    viewport: options.viewport.clone(),
+                },
+            )
+        })
+        .inspect_err(|_| restore(options))
+}
+
+/// Poll `input`, or else `resizes`, which are reported separately. This ends once `input` does,
+/// even though resizes could still be reported.
+#[cfg(any(feature = "termion", feature = "termwiz"))]
+fn poll_with_resizes<T>(
+    input: &mut (impl Stream<Item = T> + Unpin),
+    resizes: &mut SelectAll<BoxStream<'static, T>>,
+    cx: &mut Context<'_>,
+) -> Poll<Option<T>> {
+    match input.poll_next_unpin(cx) {
+        Poll::Pending => match resizes.poll_next_unpin(cx) {
+            Poll::Ready(Some(resize)) => Poll::Ready(Some(resize)),
+            _ => Poll::Pending,
+        },
+        ready => ready,
+    }
+}
+
 /// Specific functionality a backend's event must have for ratatui-elm to work.
 pub trait Event {
+    /// What [`Event::to_input_with`] remembers of earlier events to convert later ones, such as
+    /// which mouse buttons are held.
+    type InputState: Default + Send + 'static;
+
     /// Check if the event is a resize event.
     fn resize(&self) -> Option<(u16, u16)>;
+
+    /// Check if this event can be replaced by `next` when the two arrive back to back.
+    ///
+    /// Used by [`App::coalesce_events`](crate::App::coalesce_events). By default, only resize
+    /// events coalesce.
+    fn coalesces_with(&self, next: &Self) -> bool {
+        self.resize().is_some() && next.resize().is_some()
+    }
+
+    /// Convert the event to the same [form](crate::input::Event) for every backend, if it has one.
+    ///
+    /// Used by [`App::on_input`](crate::App::on_input). By default, only resize events are
+    /// converted.
+    fn to_input(&self) -> Option<crate::input::Event> {
+        self.resize()
+            .map(|(width, height)| crate::input::Event::Resize(width, height))
+    }
+
+    /// Like [`Event::to_input`], given what `state` remembers of the events converted before this
+    /// one.
+    ///
+    /// Used by [`App::on_input`](crate::App::on_input) and [`DynBackend`]. By default, `state` is
+    /// ignored.
+    fn to_input_with(&self, _state: &mut Self::InputState) -> Option<crate::input::Event> {
+        self.to_input()
+    }
 }
 
-/// Rewrite of [`Default`].
+/// Fallible rewrite of [`Default`], for the terminal set up with the given options.
+///
+/// Creating an event stream fails if the terminal can't be read from.
 ///
 /// This is only necessary because crossterm's impl of [`Backend::EventStream`] uses [`futures::stream::Fuse`], which doesn't provide a blanked `Default` impl. ☹️
-pub trait New {
-    fn new() -> Self;
+pub trait New: Sized {
+    fn new(options: &TerminalOptions) -> std::io::Result<Self>;
 }
@@ -2,22 +2,179 @@ use async_signal::{Signal, Signals};
 use byor::channel::mpsc::{RuntimeMpsc, UnboundedSender};
 use futures::{
     Stream, StreamExt,
-    stream::{BoxStream, FusedStream, SelectAll},
+    stream::{BoxStream, Fuse, FusedStream, SelectAll},
 };
 use ratatui::{
-    Terminal,
+    Viewport,
+    backend::{Backend, ClearType, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size},
     termion::{
-        event::Event as TermionEvent,
+        event::{Event as TermionEvent, Key, MouseButton, MouseEvent},
         input::TermRead,
-        raw::{IntoRawMode, RawTerminal},
-        screen::{AlternateScreen, IntoAlternateScreen},
-        terminal_size,
+        raw::IntoRawMode,
+        screen::{ToAlternateScreen, ToMainScreen},
+        terminal_size_fd, terminal_size_pixels_fd,
     },
 };
-use std::{io::Result, marker::PhantomData};
+use std::{
+    fs::File,
+    io::{self, PipeReader, PipeWriter, Read, Result, Write},
+    marker::PhantomData,
+    os::fd::{AsFd, AsRawFd, OwnedFd},
+    sync::{Mutex, PoisonError},
+    thread::JoinHandle,
+};
+
+use super::{Input, Output, TerminalOptions, Tty, cursor::Cursor};
+use crate::input;
+
+/// Enables mouse reporting, as `termion::input::MouseTerminal` does.
+const ENABLE_MOUSE: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+/// Disables mouse reporting, as `termion::input::MouseTerminal` does when dropped.
+const DISABLE_MOUSE: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+/// The terminals set up, from [`Backend::init`](super::Backend::init) until
+/// [`Backend::restore`](super::Backend::restore) drops them to disable raw mode, along with the
+/// [`TerminalOptions::tty`] they were set up for.
+///
+/// These are in raw mode, unless they are a [`Tty`] made of a reader and writer, which has no mode
+/// to set. They are kept here rather than in the backend so that a terminal can be restored from a
+/// panic hook while its backend is still alive.
+static RAW_TERMINALS: Mutex<Vec<(Option<Tty>, RawTerminalOutput)>> = Mutex::new(Vec::new());
+
+/// The output of a terminal in [`RAW_TERMINALS`].
+type RawTerminalOutput = Box<dyn Write + Send>;
+
+/// A termion backend that renders to [`TerminalOptions::render_to`], or to
+/// [`TerminalOptions::tty`].
+///
+/// This wraps ratatui's termion backend, which always reads the terminal size from standard
+/// output, and asks the controlling terminal where the cursor is whenever it needs to know, so
+/// that the terminal actually being rendered to is used instead, and only asked once for the
+/// cursor, before anything else reads its input.
+pub struct TermionBackend {
+    inner: ratatui::backend::TermionBackend<Output>,
+    size: SizeSource,
+    cursor: Cursor,
+}
+
+impl TermionBackend {
+    fn new(options: &TerminalOptions, mut output: Output) -> Result<Self> {
+        let cursor = match options.viewport {
+            // Asked now, as the input thread that would race for the reply doesn't exist yet.
+            Viewport::Inline(_) => Cursor::new(super::io::cursor_position(
+                &mut output,
+                &Input::open(options.tty.as_ref())?,
+            )?),
+            _ => Cursor::new(Position::ORIGIN),
+        };
+        Ok(Self {
+            size: SizeSource::new(options, &output)?,
+            inner: ratatui::backend::TermionBackend::new(output),
+            cursor,
+        })
+    }
+}
+
+/// Where the size of the terminal being rendered to is read from.
+enum SizeSource {
+    /// A duplicate of the output's file descriptor.
+    Output(OwnedFd),
+    /// The [`TerminalOptions::tty`], which may have no file descriptor to read it from.
+    Tty(Tty),
+}
+
+impl SizeSource {
+    fn new(options: &TerminalOptions, output: &Output) -> Result<Self> {
+        Ok(match &options.tty {
+            Some(tty) => Self::Tty(tty.clone()),
+            None => Self::Output(output.as_fd().try_clone_to_owned()?),
+        })
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        match self {
+            Self::Output(fd) => terminal_size_fd(fd),
+            Self::Tty(tty) => tty.size(),
+        }
+    }
+
+    /// The size in pixels, or zero if unknown.
+    fn pixels(&self) -> Result<(u16, u16)> {
+        match self {
+            Self::Output(fd) => terminal_size_pixels_fd(fd),
+            Self::Tty(tty) => tty.device().map_or(Ok((0, 0)), terminal_size_pixels_fd),
+        }
+    }
+}
+
+impl Backend for TermionBackend {
+    fn draw<'a, I>(&mut self, content: I) -> Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(self.cursor.draw(content))
+    }
 
-pub type TermionBackend =
-    ratatui::backend::TermionBackend<AlternateScreen<RawTerminal<std::io::Stdout>>>;
+    fn append_lines(&mut self, n: u16) -> Result<()> {
+        self.cursor.append_lines(n, self.size()?);
+        self.inner.append_lines(n)
+    }
+
+    fn hide_cursor(&mut self) -> Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> Result<Position> {
+        Ok(self.cursor.position(self.size()?))
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> Result<()> {
+        let position = position.into();
+        self.cursor.set(position);
+        self.inner.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> Result<Size> {
+        self.size.size().map(Size::from)
+    }
+
+    fn window_size(&mut self) -> Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.size.size()?.into(),
+            pixels: self.size.pixels()?.into(),
+        })
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Backend::flush(&mut self.inner)
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_up(&mut self, region: std::ops::Range<u16>, line_count: u16) -> Result<()> {
+        self.cursor.scroll_region();
+        self.inner.scroll_region_up(region, line_count)
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_down(&mut self, region: std::ops::Range<u16>, line_count: u16) -> Result<()> {
+        self.cursor.scroll_region();
+        self.inner.scroll_region_down(region, line_count)
+    }
+}
 
 /// Termion events _or_ resize events.
 ///
@@ -37,50 +194,164 @@ where
     type Error = std::io::Error;
     type EventStream = TermionEventStream<R>;
 
-    fn init() -> ratatui::Terminal<Self> {
-        let stdout = std::io::stdout()
-            .into_raw_mode()
-            .unwrap()
-            .into_alternate_screen()
-            .unwrap();
-        Terminal::new(TermionBackend::new(stdout)).unwrap()
+    fn init(options: &TerminalOptions) -> Result<ratatui::Terminal<Self>> {
+        super::init(options, setup, <Self as super::Backend<R>>::restore)
     }
 
-    fn restore() {}
+    fn restore(options: &TerminalOptions) {
+        let mut raw_terminals = RAW_TERMINALS.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(index) = raw_terminals
+            .iter()
+            .position(|(tty, _)| *tty == options.tty)
+        else {
+            return;
+        };
+        let (_, mut output) = raw_terminals.swap_remove(index);
+        drop(raw_terminals);
+        if let Err(e) = try_restore(options, &mut *output) {
+            eprintln!("Failed to restore terminal: {e}");
+        }
+        // Raw mode is disabled when `output` is dropped.
+    }
+}
+
+/// Set the terminal up as `options` ask, returning a backend that renders to it.
+pub(super) fn setup(options: &TerminalOptions) -> Result<TermionBackend> {
+    let output = Output::open(options)?;
+    let raw_terminal: RawTerminalOutput = match &options.tty {
+        Some(tty) if tty.device().is_none() => Box::new(output),
+        _ => Box::new(output.into_raw_mode()?),
+    };
+    RAW_TERMINALS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((options.tty.clone(), raw_terminal));
+    let mut output = Output::open(options)?;
+    if options.viewport == Viewport::Fullscreen {
+        write!(output, "{ToAlternateScreen}")?;
+    }
+    // termion can't parse pasted text, focus changes or kitty keyboard events, so those options
+    // are ignored.
+    if options.mouse {
+        write!(output, "{ENABLE_MOUSE}")?;
+    }
+    TermionBackend::new(options, output)
+}
+
+fn try_restore(options: &TerminalOptions, output: &mut dyn Write) -> Result<()> {
+    if options.mouse {
+        write!(output, "{DISABLE_MOUSE}")?;
+    }
+    if options.viewport == Viewport::Fullscreen {
+        write!(output, "{ToMainScreen}")?;
+    }
+    output.flush()
 }
 
 /// An asynchronous stream of termion events.
+///
+/// Input is read on a dedicated thread, which is stopped and joined when the stream is dropped so
+/// that nothing keeps reading from the terminal once the application exits. The stream ends once
+/// the input does.
 pub struct TermionEventStream<R: RuntimeMpsc + Unpin> {
-    select: SelectAll<BoxStream<'static, Result<Event>>>,
+    input: Fuse<BoxStream<'static, Result<Event>>>,
+    /// Resizes, reported separately from the input.
+    resizes: SelectAll<BoxStream<'static, Result<Event>>>,
+    /// Closed to stop the input thread.
+    stop: Option<PipeWriter>,
+    thread: Option<JoinHandle<()>>,
     _marker: PhantomData<R>,
 }
 
+/// Terminal input that reaches its end once the write end of `stop` is closed.
+struct StoppableInput {
+    /// Read directly rather than through [`Input`], as the buffer of [`Stdin`](std::io::Stdin)
+    /// would keep the rest of an escape sequence from `poll`, delaying it until the next key.
+    input: File,
+    stop: PipeReader,
+}
+
+impl Read for StoppableInput {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut fds = [&self.input.as_fd(), &self.stop.as_fd()].map(|fd| libc::pollfd {
+            fd: fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        loop {
+            // SAFETY: `fds` is an array of valid `pollfd`s of the given length.
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } < 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(e);
+            }
+            let [input, stop] = fds;
+            if stop.revents != 0 {
+                return Ok(0);
+            }
+            if input.revents != 0 {
+                return self.input.read(buf);
+            }
+        }
+    }
+}
+
 impl<R: RuntimeMpsc + Unpin> super::New for TermionEventStream<R>
 where
     <R as RuntimeMpsc>::UnboundedReceiver<Result<TermionEvent>>: Send + 'static,
     <R as RuntimeMpsc>::UnboundedSender<Result<TermionEvent>>: Send + 'static,
 {
-    fn new() -> Self {
+    fn new(options: &TerminalOptions) -> Result<Self> {
+        let input = Input::open(options.tty.as_ref())?;
+        let mut signals = Signals::new([Signal::Winch])?;
+        let output = Output::open(options)?;
+
+        let (stop_reader, stop) = io::pipe()?;
+        let input = StoppableInput {
+            input: input.as_fd().try_clone_to_owned()?.into(),
+            stop: stop_reader,
+        };
+
         let (tx, rx) = R::unbounded_channel();
-        std::thread::spawn(move || {
-            for event in std::io::stdin().events() {
-                tx.send(event).unwrap();
+        let thread = std::thread::spawn(move || {
+            for event in input.events() {
+                if tx.send(event).is_err() {
+                    break;
+                }
             }
         });
 
-        let mut select: SelectAll<BoxStream<'static, Result<Event>>> = SelectAll::new();
-        select.push(Box::pin(rx.map(|r| r.map(Event::Termion))));
-        select.push(Box::pin(async_stream::stream! {
-            let mut signals = Signals::new([Signal::Winch]).unwrap();
+        let size = SizeSource::new(options, &output)?;
+        let mut resizes: SelectAll<BoxStream<'static, Result<Event>>> = SelectAll::new();
+        resizes.push(Box::pin(async_stream::stream! {
             while signals.next().await.is_some() {
-                let (x, y) = terminal_size()?;
+                let (x, y) = size.size()?;
                 yield Ok(Event::Resize(x, y));
             }
         }));
+        if let Some(tty) = &options.tty {
+            resizes.push(Box::pin(
+                tty.resizes().map(|(x, y)| Ok(Event::Resize(x, y))),
+            ));
+        }
 
-        Self {
-            select,
+        Ok(Self {
+            input: rx.map(|r| r.map(Event::Termion)).boxed().fuse(),
+            resizes,
+            stop: Some(stop),
+            thread: Some(thread),
             _marker: PhantomData,
+        })
+    }
+}
+
+impl<R: RuntimeMpsc + Unpin> Drop for TermionEventStream<R> {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
     }
 }
@@ -92,17 +363,20 @@ impl<R: RuntimeMpsc + Unpin> Stream for TermionEventStream<R> {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        self.select.poll_next_unpin(cx)
+        let this = &mut *self;
+        super::poll_with_resizes(&mut this.input, &mut this.resizes, cx)
     }
 }
 
 impl<R: RuntimeMpsc + Unpin> FusedStream for TermionEventStream<R> {
     fn is_terminated(&self) -> bool {
-        self.select.is_terminated()
+        self.input.is_terminated()
     }
 }
 
 impl super::Event for Event {
+    type InputState = ();
+
     fn resize(&self) -> Option<(u16, u16)> {
         if let Event::Resize(width, height) = self {
             Some((*width, *height))
@@ -110,4 +384,229 @@ impl super::Event for Event {
             None
         }
     }
+
+    fn coalesces_with(&self, next: &Self) -> bool {
+        matches!(
+            (self, next),
+            (Event::Resize(..), Event::Resize(..))
+                | (
+                    Event::Termion(TermionEvent::Mouse(MouseEvent::Hold(..))),
+                    Event::Termion(TermionEvent::Mouse(MouseEvent::Hold(..)))
+                )
+        )
+    }
+
+    fn to_input(&self) -> Option<input::Event> {
+        to_input(self)
+    }
+}
+
+/// Convert a termion event to its [`input`] form, if it has one.
+fn to_input(event: &Event) -> Option<input::Event> {
+    let event = match event {
+        Event::Termion(event) => event,
+        Event::Resize(width, height) => return Some(input::Event::Resize(*width, *height)),
+    };
+    Some(match event {
+        TermionEvent::Key(key) => {
+            let (code, modifiers) = key_code(*key)?;
+            input::Event::Key(input::Key::new(code, modifiers))
+        }
+        // termion's coordinates are one-based.
+        TermionEvent::Mouse(mouse) => {
+            let (kind, column, row) = match *mouse {
+                MouseEvent::Press(button, column, row) => {
+                    let kind = match button {
+                        MouseButton::Left => input::MouseKind::Down(input::MouseButton::Left),
+                        MouseButton::Right => input::MouseKind::Down(input::MouseButton::Right),
+                        MouseButton::Middle => input::MouseKind::Down(input::MouseButton::Middle),
+                        MouseButton::WheelUp => input::MouseKind::ScrollUp,
+                        MouseButton::WheelDown => input::MouseKind::ScrollDown,
+                        MouseButton::WheelLeft => input::MouseKind::ScrollLeft,
+                        MouseButton::WheelRight => input::MouseKind::ScrollRight,
+                    };
+                    (kind, column, row)
+                }
+                MouseEvent::Release(column, row) => (input::MouseKind::Up(None), column, row),
+                MouseEvent::Hold(column, row) => (input::MouseKind::Drag(None), column, row),
+            };
+            input::Event::Mouse(input::Mouse {
+                kind,
+                column: column.saturating_sub(1),
+                row: row.saturating_sub(1),
+                modifiers: input::Modifiers::NONE,
+            })
+        }
+        TermionEvent::Unsupported(_) => return None,
+    })
+}
+
+/// Convert a key, along with the modifiers it implies.
+fn key_code(key: Key) -> Option<(input::KeyCode, input::Modifiers)> {
+    use input::{KeyCode, Modifiers};
+    Some(match key {
+        Key::Char('\n') => (KeyCode::Enter, Modifiers::NONE),
+        Key::Char('\t') => (KeyCode::Tab, Modifiers::NONE),
+        Key::Char(c) => (KeyCode::Char(c), Modifiers::NONE),
+        Key::Alt(c) => (KeyCode::Char(c), Modifiers::ALT),
+        Key::Ctrl(c) => (KeyCode::Char(c), Modifiers::CONTROL),
+        // Reported for Ctrl+Space.
+        Key::Null => (KeyCode::Char(' '), Modifiers::CONTROL),
+        Key::Esc => (KeyCode::Esc, Modifiers::NONE),
+        Key::Backspace => (KeyCode::Backspace, Modifiers::NONE),
+        Key::BackTab => (KeyCode::BackTab, Modifiers::SHIFT),
+        Key::Delete => (KeyCode::Delete, Modifiers::NONE),
+        Key::Insert => (KeyCode::Insert, Modifiers::NONE),
+        Key::Home => (KeyCode::Home, Modifiers::NONE),
+        Key::CtrlHome => (KeyCode::Home, Modifiers::CONTROL),
+        Key::End => (KeyCode::End, Modifiers::NONE),
+        Key::CtrlEnd => (KeyCode::End, Modifiers::CONTROL),
+        Key::PageUp => (KeyCode::PageUp, Modifiers::NONE),
+        Key::PageDown => (KeyCode::PageDown, Modifiers::NONE),
+        Key::Up => (KeyCode::Up, Modifiers::NONE),
+        Key::ShiftUp => (KeyCode::Up, Modifiers::SHIFT),
+        Key::AltUp => (KeyCode::Up, Modifiers::ALT),
+        Key::CtrlUp => (KeyCode::Up, Modifiers::CONTROL),
+        Key::Down => (KeyCode::Down, Modifiers::NONE),
+        Key::ShiftDown => (KeyCode::Down, Modifiers::SHIFT),
+        Key::AltDown => (KeyCode::Down, Modifiers::ALT),
+        Key::CtrlDown => (KeyCode::Down, Modifiers::CONTROL),
+        Key::Left => (KeyCode::Left, Modifiers::NONE),
+        Key::ShiftLeft => (KeyCode::Left, Modifiers::SHIFT),
+        Key::AltLeft => (KeyCode::Left, Modifiers::ALT),
+        Key::CtrlLeft => (KeyCode::Left, Modifiers::CONTROL),
+        Key::Right => (KeyCode::Right, Modifiers::NONE),
+        Key::ShiftRight => (KeyCode::Right, Modifiers::SHIFT),
+        Key::AltRight => (KeyCode::Right, Modifiers::ALT),
+        Key::CtrlRight => (KeyCode::Right, Modifiers::CONTROL),
+        Key::F(n) => (KeyCode::F(n), Modifiers::NONE),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::{KeyCode as Code, Modifiers as Mods};
+
+    #[test]
+    fn keys() {
+        let cases = [
+            (Key::Char('a'), Some((Code::Char('a'), Mods::NONE))),
+            (Key::Char('A'), Some((Code::Char('A'), Mods::NONE))),
+            (Key::Char('\n'), Some((Code::Enter, Mods::NONE))),
+            (Key::Char('\t'), Some((Code::Tab, Mods::NONE))),
+            (Key::Alt('x'), Some((Code::Char('x'), Mods::ALT))),
+            (Key::Ctrl('c'), Some((Code::Char('c'), Mods::CONTROL))),
+            (Key::Null, Some((Code::Char(' '), Mods::CONTROL))),
+            (Key::Esc, Some((Code::Esc, Mods::NONE))),
+            (Key::Backspace, Some((Code::Backspace, Mods::NONE))),
+            (Key::BackTab, Some((Code::BackTab, Mods::SHIFT))),
+            (Key::Delete, Some((Code::Delete, Mods::NONE))),
+            (Key::Insert, Some((Code::Insert, Mods::NONE))),
+            (Key::Home, Some((Code::Home, Mods::NONE))),
+            (Key::CtrlHome, Some((Code::Home, Mods::CONTROL))),
+            (Key::End, Some((Code::End, Mods::NONE))),
+            (Key::CtrlEnd, Some((Code::End, Mods::CONTROL))),
+            (Key::PageUp, Some((Code::PageUp, Mods::NONE))),
+            (Key::PageDown, Some((Code::PageDown, Mods::NONE))),
+            (Key::Up, Some((Code::Up, Mods::NONE))),
+            (Key::ShiftUp, Some((Code::Up, Mods::SHIFT))),
+            (Key::AltUp, Some((Code::Up, Mods::ALT))),
+            (Key::CtrlUp, Some((Code::Up, Mods::CONTROL))),
+            (Key::Down, Some((Code::Down, Mods::NONE))),
+            (Key::ShiftDown, Some((Code::Down, Mods::SHIFT))),
+            (Key::AltDown, Some((Code::Down, Mods::ALT))),
+            (Key::CtrlDown, Some((Code::Down, Mods::CONTROL))),
+            (Key::Left, Some((Code::Left, Mods::NONE))),
+            (Key::ShiftLeft, Some((Code::Left, Mods::SHIFT))),
+            (Key::AltLeft, Some((Code::Left, Mods::ALT))),
+            (Key::CtrlLeft, Some((Code::Left, Mods::CONTROL))),
+            (Key::Right, Some((Code::Right, Mods::NONE))),
+            (Key::ShiftRight, Some((Code::Right, Mods::SHIFT))),
+            (Key::AltRight, Some((Code::Right, Mods::ALT))),
+            (Key::CtrlRight, Some((Code::Right, Mods::CONTROL))),
+            (Key::F(12), Some((Code::F(12), Mods::NONE))),
+        ];
+        for (key, expected) in cases {
+            let expected =
+                expected.map(|(code, mods)| input::Event::Key(input::Key::new(code, mods)));
+            assert_eq!(
+                to_input(&Event::Termion(TermionEvent::Key(key))),
+                expected,
+                "{key:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn mouse_events_are_zero_based() {
+        use input::{MouseButton as Button, MouseKind as Kind};
+        let cases = [
+            (
+                MouseEvent::Press(MouseButton::Left, 4, 8),
+                Kind::Down(Button::Left),
+            ),
+            (
+                MouseEvent::Press(MouseButton::Right, 4, 8),
+                Kind::Down(Button::Right),
+            ),
+            (
+                MouseEvent::Press(MouseButton::Middle, 4, 8),
+                Kind::Down(Button::Middle),
+            ),
+            (
+                MouseEvent::Press(MouseButton::WheelUp, 4, 8),
+                Kind::ScrollUp,
+            ),
+            (
+                MouseEvent::Press(MouseButton::WheelDown, 4, 8),
+                Kind::ScrollDown,
+            ),
+            (
+                MouseEvent::Press(MouseButton::WheelLeft, 4, 8),
+                Kind::ScrollLeft,
+            ),
+            (
+                MouseEvent::Press(MouseButton::WheelRight, 4, 8),
+                Kind::ScrollRight,
+            ),
+            (MouseEvent::Release(4, 8), Kind::Up(None)),
+            (MouseEvent::Hold(4, 8), Kind::Drag(None)),
+        ];
+        for (mouse, kind) in cases {
+            let expected = input::Mouse {
+                kind,
+                column: 3,
+                row: 7,
+                modifiers: Mods::NONE,
+            };
+            let event = Event::Termion(TermionEvent::Mouse(mouse));
+            assert_eq!(
+                to_input(&event),
+                Some(input::Event::Mouse(expected)),
+                "{mouse:?}"
+            );
+        }
+        // Coordinates below one can't be reported, but shouldn't underflow either.
+        let event = Event::Termion(TermionEvent::Mouse(MouseEvent::Hold(0, 0)));
+        assert!(matches!(
+            to_input(&event),
+            Some(input::Event::Mouse(input::Mouse {
+                column: 0,
+                row: 0,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn other_events() {
+        assert_eq!(
+            to_input(&Event::Resize(80, 24)),
+            Some(input::Event::Resize(80, 24))
+        );
+        let unsupported = Event::Termion(TermionEvent::Unsupported(b"\x1b[?".to_vec()));
+        assert_eq!(to_input(&unsupported), None);
+    }
 }
@@ -1,20 +1,159 @@
-use std::{marker::PhantomData, pin::Pin};
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, IsTerminal},
+    marker::PhantomData,
+    os::fd::AsFd,
+    pin::Pin,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
 
 use byor::channel::mpsc::{RuntimeMpsc, UnboundedSender};
 use futures::{
     Stream, StreamExt,
-    stream::{Fuse, FusedStream},
+    stream::{BoxStream, Fuse, FusedStream, SelectAll},
 };
 use ratatui::{
-    Terminal,
-    backend::TermwizBackend,
+    Terminal, Viewport,
+    backend::{Backend, ClearType, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size},
     termwiz::{
         self,
-        caps::Capabilities,
-        input::InputEvent,
-        terminal::{Terminal as _, UnixTerminal, buffered::BufferedTerminal},
+        caps::{Capabilities, ProbeHints},
+        color::ColorAttribute,
+        input::{InputEvent, KeyCode, Modifiers, MouseButtons},
+        surface::{Change, Position as SurfacePosition, SequenceNo},
+        terminal::{
+            SystemTerminal, Terminal as _, TerminalWaker, UnixTerminal, buffered::BufferedTerminal,
+        },
     },
 };
+
+use super::{Output, TerminalOptions, Tty};
+use crate::input;
+
+/// Terminals opened before raw mode was enabled, from [`Backend::init`](super::Backend::init)
+/// until [`Backend::restore`](super::Backend::restore) drops them, along with the
+/// [`TerminalOptions::tty`] they were opened for.
+///
+/// termwiz terminals return to the mode they were opened in when dropped, and leave the alternate
+/// screen if they entered it, so this is what restores a terminal. They are kept here rather than
+/// in the backend so that a terminal can be restored from a panic hook while its backend is still
+/// alive.
+static ORIGINAL_TERMINALS: Mutex<Vec<(Option<Tty>, UnixTerminal)>> = Mutex::new(Vec::new());
+
+/// A termwiz backend that renders to [`TerminalOptions::render_to`], or to
+/// [`TerminalOptions::tty`].
+///
+/// This wraps ratatui's termwiz backend, which can only draw full-screen, so that it also supports
+/// inline and fixed viewports.
+pub struct TermwizBackend {
+    inner: ratatui::backend::TermwizBackend,
+    /// The last change flushed to the terminal, when not drawing full-screen.
+    ///
+    /// termwiz clears the screen and repaints it from scratch on its first flush, which would wipe
+    /// everything around an inline or fixed viewport, so changes are flushed manually instead.
+    seqno: Option<SequenceNo>,
+}
+
+impl TermwizBackend {
+    /// Returns a reference to the buffered terminal used by the backend.
+    pub fn buffered_terminal(&self) -> &BufferedTerminal<SystemTerminal> {
+        self.inner.buffered_terminal()
+    }
+
+    /// Returns a mutable reference to the buffered terminal used by the backend.
+    pub fn buffered_terminal_mut(&mut self) -> &mut BufferedTerminal<SystemTerminal> {
+        self.inner.buffered_terminal_mut()
+    }
+}
+
+impl Backend for TermwizBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        self.buffered_terminal_mut()
+            .add_change("\n".repeat(n.into()));
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.inner.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.inner.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        let change = match clear_type {
+            ClearType::All => return self.inner.clear(),
+            ClearType::AfterCursor => Change::ClearToEndOfScreen(ColorAttribute::Default),
+            ClearType::UntilNewLine => Change::ClearToEndOfLine(ColorAttribute::Default),
+            _ => return self.inner.clear_region(clear_type),
+        };
+        self.buffered_terminal_mut().add_change(change);
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.inner.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.inner.window_size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let Some(seqno) = self.seqno else {
+            return self.inner.flush();
+        };
+        let buffered_terminal = self.buffered_terminal_mut();
+        let (seqno, changes) = buffered_terminal.get_changes(seqno);
+        let changes = changes.into_owned();
+        buffered_terminal
+            .terminal()
+            .render(&changes)
+            .map_err(io::Error::other)?;
+        buffered_terminal.flush_changes_older_than(seqno);
+        self.seqno = Some(seqno);
+        Ok(())
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_up(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        self.inner.scroll_region_up(region, amount)
+    }
+
+    #[cfg(feature = "scrolling-regions")]
+    fn scroll_region_down(&mut self, region: std::ops::Range<u16>, amount: u16) -> io::Result<()> {
+        self.inner.scroll_region_down(region, amount)
+    }
+}
+
 impl<R: RuntimeMpsc + Unpin> super::Backend<R> for TermwizBackend
 where
     <R as RuntimeMpsc>::UnboundedReceiver<termwiz::Result<InputEvent>>: Send + 'static,
@@ -24,59 +163,196 @@ where
     type Error = termwiz::Error;
     type EventStream = TermwizEventStream<R>;
 
-    fn init() -> Terminal<Self> {
-        let hook = std::panic::take_hook();
-        std::panic::set_hook(Box::new(move |info| {
-            <Self as super::Backend<R>>::restore();
-            hook(info);
-        }));
-
-        Terminal::new(TermwizBackend::new().expect("Failed to create backend"))
-            .expect("Failed to create terminal")
+    fn init(options: &TerminalOptions) -> io::Result<Terminal<Self>> {
+        super::init(options, setup, <Self as super::Backend<R>>::restore)
     }
 
-    fn restore() {
-        match new_terminal() {
-            Ok(mut terminal) => {
-                if let Err(e) = terminal.exit_alternate_screen() {
-                    eprintln!("Failed to leave alternate screen: {e}");
-                }
-                if let Err(e) = terminal.set_cooked_mode() {
-                    eprintln!("Failed to set raw mode: {e}");
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to create terminal: {e}");
-            }
+    fn restore(options: &TerminalOptions) {
+        let mut original_terminals = ORIGINAL_TERMINALS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = original_terminals
+            .iter()
+            .position(|(tty, _)| *tty == options.tty)
+        {
+            // Dropping the original terminal also disables mouse reporting and bracketed paste if
+            // its capabilities say they were enabled.
+            drop(original_terminals.swap_remove(index));
         }
     }
 
+    fn check_tty(tty: &Tty, _options: &TerminalOptions) -> io::Result<()> {
+        device(tty).map(drop)
+    }
+
     fn handle_resize(&mut self, width: u16, height: u16) {
         self.buffered_terminal_mut()
             .resize(width as usize, height as usize);
     }
 }
 
-fn new_terminal() -> termwiz::Result<UnixTerminal> {
-    UnixTerminal::new_from_stdio(Capabilities::new_from_env()?)
+/// Set the terminal up as `options` ask, returning a backend that renders to it.
+pub(super) fn setup(options: &TerminalOptions) -> io::Result<TermwizBackend> {
+    if let Some(tty) = &options.tty {
+        device(tty)?;
+    }
+    try_setup(options).map_err(io::Error::other)
+}
+
+fn try_setup(options: &TerminalOptions) -> Result<TermwizBackend, Box<dyn Error + Send + Sync>> {
+    let viewport = &options.viewport;
+    let mut original = output_terminal(options)?;
+    // Entered through the original terminal so that only restoring leaves it.
+    if *viewport == Viewport::Fullscreen {
+        original.enter_alternate_screen()?;
+        original.flush()?;
+    }
+    ORIGINAL_TERMINALS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((options.tty.clone(), original));
+    // Raw mode enables mouse reporting and bracketed paste according to the capabilities.
+    let mut terminal = output_terminal(options)?;
+    terminal.set_raw_mode()?;
+    let mut buffered_terminal = BufferedTerminal::new(terminal)?;
+    let seqno = if *viewport == Viewport::Fullscreen {
+        None
+    } else {
+        // termwiz only tracks the cursor within its own buffer, so it has to be told where the
+        // cursor actually is for an inline viewport to be placed under it.
+        let (x, y) = match viewport {
+            Viewport::Inline(_) => cursor_position(options.tty.as_ref())?,
+            _ => (0, 0),
+        };
+        // This change is also what makes the sequence number nonzero, which termwiz would
+        // otherwise take to mean that nothing was ever flushed.
+        buffered_terminal.add_change(Change::CursorPosition {
+            x: SurfacePosition::Absolute(x),
+            y: SurfacePosition::Absolute(y),
+        });
+        Some(buffered_terminal.current_seqno())
+    };
+    Ok(TermwizBackend {
+        inner: ratatui::backend::TermwizBackend::with_buffered_terminal(buffered_terminal),
+        seqno,
+    })
+}
+
+/// The terminal's capabilities, with mouse reporting and bracketed paste only enabled if
+/// requested.
+fn capabilities(options: &TerminalOptions) -> termwiz::Result<Capabilities> {
+    Capabilities::new_with_hints(
+        ProbeHints::new_from_env()
+            .mouse_reporting(Some(options.mouse))
+            .bracketed_paste(Some(options.paste)),
+    )
+}
+
+/// Asks `tty`, or the controlling terminal, where its cursor is, as a zero-based
+/// `(column, row)`.
+fn cursor_position(tty: Option<&Tty>) -> io::Result<(usize, usize)> {
+    let mut tty = match tty {
+        Some(tty) => device(tty)?.try_clone()?,
+        None => super::io::open_tty()?,
+    };
+    let input = tty.try_clone()?;
+    let (column, row) = super::io::cursor_position(&mut tty, &input)?;
+    Ok((column.into(), row.into()))
+}
+
+/// Opens the terminal that `options` render to.
+fn output_terminal(
+    options: &TerminalOptions,
+) -> Result<UnixTerminal, Box<dyn Error + Send + Sync>> {
+    let output = Output::open(options)?;
+    Ok(UnixTerminal::new_with(
+        capabilities(options)?,
+        &output.as_fd(),
+        &output.as_fd(),
+    )?)
+}
+
+/// Opens `tty` if given, or else the terminal on stdio, or on `/dev/tty` if either stdin or stdout
+/// isn't a terminal.
+fn new_terminal(caps: Capabilities, tty: Option<&Tty>) -> termwiz::Result<UnixTerminal> {
+    if let Some(tty) = tty {
+        let tty = device(tty)?;
+        UnixTerminal::new_with(caps, tty, tty)
+    } else if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        UnixTerminal::new_from_stdio(caps)
+    } else {
+        UnixTerminal::new(caps)
+    }
+}
+
+/// The terminal device of `tty`, as termwiz can't use a reader and writer.
+fn device(tty: &Tty) -> io::Result<&File> {
+    tty.device().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "termwiz can only run in a terminal device, not a reader and writer",
+        )
+    })
+}
+
+/// A terminal to read input from. It enables nothing, so dropping it disables nothing either.
+fn input_terminal(tty: Option<&Tty>) -> termwiz::Result<UnixTerminal> {
+    new_terminal(capabilities(&TerminalOptions::default())?, tty)
 }
 
 impl super::Event for InputEvent {
+    /// The mouse buttons held as of the last mouse event.
+    type InputState = MouseButtons;
+
     fn resize(&self) -> Option<(u16, u16)> {
         if let InputEvent::Resized { cols, rows } = self {
-            BufferedTerminal::new(new_terminal().unwrap())
-                .unwrap()
-                .resize(*cols, *rows);
             Some((*cols as u16, *rows as u16))
         } else {
             None
         }
     }
+
+    fn coalesces_with(&self, next: &Self) -> bool {
+        match (self, next) {
+            (InputEvent::Resized { .. }, InputEvent::Resized { .. }) => true,
+            // Consecutive mouse events with the same buttons held are moves or drags, unless they
+            // are wheel ticks, each of which scrolls.
+            (InputEvent::Mouse(a), InputEvent::Mouse(b)) => {
+                let wheel = MouseButtons::VERT_WHEEL | MouseButtons::HORZ_WHEEL;
+                !a.mouse_buttons.intersects(wheel)
+                    && a.mouse_buttons == b.mouse_buttons
+                    && a.modifiers == b.modifiers
+            }
+            _ => false,
+        }
+    }
+
+    fn to_input(&self) -> Option<input::Event> {
+        to_input(self, &mut MouseButtons::default())
+    }
+
+    fn to_input_with(&self, held: &mut MouseButtons) -> Option<input::Event> {
+        to_input(self, held)
+    }
 }
 
+/// How long the input thread waits for input before checking whether it should stop, in case
+/// [`TerminalWaker::wake`] fails to interrupt its wait.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An asynchronous stream of termwiz events.
+///
+/// Input is read on a dedicated thread, which is stopped and joined when the stream is dropped so
+/// that nothing keeps reading from the terminal once the application exits.
 pub struct TermwizEventStream<R: RuntimeMpsc + Unpin> {
     #[allow(clippy::type_complexity)]
     rx: Pin<Box<Fuse<R::UnboundedReceiver<termwiz::Result<InputEvent>>>>>,
+    /// Resizes made with [`Tty::set_size`], which termwiz doesn't hear about.
+    resizes: SelectAll<BoxStream<'static, termwiz::Result<InputEvent>>>,
+    stop: Arc<AtomicBool>,
+    /// Interrupts the input thread's wait for input, so that it notices `stop`.
+    waker: TerminalWaker,
+    thread: Option<JoinHandle<()>>,
     _marker: PhantomData<R>,
 }
 
@@ -85,19 +361,60 @@ where
     <R as RuntimeMpsc>::UnboundedReceiver<termwiz::Result<InputEvent>>: Send + 'static,
     <R as RuntimeMpsc>::UnboundedSender<termwiz::Result<InputEvent>>: Send + 'static,
 {
-    fn new() -> Self {
+    fn new(options: &TerminalOptions) -> io::Result<Self> {
         let (tx, rx) = R::unbounded_channel();
-        let mut terminal = new_terminal().unwrap();
+        let mut terminal = input_terminal(options.tty.as_ref()).map_err(io::Error::other)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let waker = terminal.waker();
 
-        std::thread::spawn(move || {
-            while let Ok(e) = terminal.poll_input(None).transpose().unwrap() {
-                tx.send(Ok(e)).unwrap();
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::SeqCst) {
+                    match terminal.poll_input(Some(STOP_POLL_INTERVAL)) {
+                        Ok(Some(e)) => {
+                            if tx.send(Ok(e)).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            let _ = tx.send(Err(e));
+                            break;
+                        }
+                    }
+                }
             }
         });
 
-        Self {
+        let mut resizes: SelectAll<BoxStream<'static, _>> = SelectAll::new();
+        if let Some(tty) = &options.tty {
+            resizes.push(Box::pin(tty.resizes().map(|(cols, rows)| {
+                Ok(InputEvent::Resized {
+                    cols: cols.into(),
+                    rows: rows.into(),
+                })
+            })));
+        }
+
+        Ok(Self {
             rx: Box::pin(rx.fuse()),
+            resizes,
+            stop,
+            waker,
+            thread: Some(thread),
             _marker: PhantomData,
+        })
+    }
+}
+
+impl<R: RuntimeMpsc + Unpin> Drop for TermwizEventStream<R> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Should waking fail, the thread still notices `stop` once its wait times out.
+        let _ = self.waker.wake();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
     }
 }
@@ -121,6 +438,385 @@ where
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        self.rx.poll_next_unpin(cx)
+        let this = &mut *self;
+        super::poll_with_resizes(&mut this.rx, &mut this.resizes, cx)
+    }
+}
+
+/// Convert a termwiz event to its [`input`] form, if it has one.
+///
+/// termwiz only reports which mouse buttons are held, so whether a button was pressed, released
+/// or dragged is told from the buttons `held` before the event, which this updates.
+fn to_input(event: &InputEvent, held: &mut MouseButtons) -> Option<input::Event> {
+    Some(match event {
+        InputEvent::Key(key) => {
+            let code = key_code(key.key, key.modifiers)?;
+            input::Event::Key(input::Key::new(code, modifiers(key.modifiers)))
+        }
+        InputEvent::Mouse(mouse) => {
+            let buttons = &mouse.mouse_buttons;
+            let kind = if buttons.contains(MouseButtons::VERT_WHEEL) {
+                if buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                    input::MouseKind::ScrollUp
+                } else {
+                    input::MouseKind::ScrollDown
+                }
+            } else if buttons.contains(MouseButtons::HORZ_WHEEL) {
+                if buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                    input::MouseKind::ScrollLeft
+                } else {
+                    input::MouseKind::ScrollRight
+                }
+            } else {
+                let now = buttons.clone()
+                    & (MouseButtons::LEFT | MouseButtons::RIGHT | MouseButtons::MIDDLE);
+                let previous = std::mem::replace(held, now.clone());
+                if let Some(pressed) = mouse_button(now.clone() - previous.clone()) {
+                    input::MouseKind::Down(pressed)
+                } else if let Some(released) = mouse_button(previous - now.clone()) {
+                    input::MouseKind::Up(Some(released))
+                } else if let Some(dragged) = mouse_button(now) {
+                    input::MouseKind::Drag(Some(dragged))
+                } else {
+                    input::MouseKind::Moved
+                }
+            };
+            // termwiz's coordinates are one-based.
+            input::Event::Mouse(input::Mouse {
+                kind,
+                column: mouse.x.saturating_sub(1),
+                row: mouse.y.saturating_sub(1),
+                modifiers: modifiers(mouse.modifiers),
+            })
+        }
+        InputEvent::Paste(text) => input::Event::Paste(input::Paste(text.clone())),
+        InputEvent::Resized { cols, rows } => input::Event::Resize(*cols as u16, *rows as u16),
+        InputEvent::PixelMouse(_) | InputEvent::Wake => return None,
+    })
+}
+
+/// One of `buttons`, preferring the left button, then the right one.
+fn mouse_button(buttons: MouseButtons) -> Option<input::MouseButton> {
+    if buttons.contains(MouseButtons::LEFT) {
+        Some(input::MouseButton::Left)
+    } else if buttons.contains(MouseButtons::RIGHT) {
+        Some(input::MouseButton::Right)
+    } else if buttons.contains(MouseButtons::MIDDLE) {
+        Some(input::MouseButton::Middle)
+    } else {
+        None
+    }
+}
+
+fn key_code(code: KeyCode, modifiers: Modifiers) -> Option<input::KeyCode> {
+    Some(match code {
+        KeyCode::Char(c) => input::KeyCode::Char(c),
+        KeyCode::Tab if modifiers.contains(Modifiers::SHIFT) => input::KeyCode::BackTab,
+        KeyCode::Tab => input::KeyCode::Tab,
+        KeyCode::Enter => input::KeyCode::Enter,
+        KeyCode::Escape => input::KeyCode::Esc,
+        KeyCode::Backspace => input::KeyCode::Backspace,
+        KeyCode::Delete => input::KeyCode::Delete,
+        KeyCode::Insert => input::KeyCode::Insert,
+        KeyCode::Home | KeyCode::KeyPadHome => input::KeyCode::Home,
+        KeyCode::End | KeyCode::KeyPadEnd => input::KeyCode::End,
+        KeyCode::PageUp | KeyCode::KeyPadPageUp => input::KeyCode::PageUp,
+        KeyCode::PageDown | KeyCode::KeyPadPageDown => input::KeyCode::PageDown,
+        KeyCode::UpArrow | KeyCode::ApplicationUpArrow => input::KeyCode::Up,
+        KeyCode::DownArrow | KeyCode::ApplicationDownArrow => input::KeyCode::Down,
+        KeyCode::LeftArrow | KeyCode::ApplicationLeftArrow => input::KeyCode::Left,
+        KeyCode::RightArrow | KeyCode::ApplicationRightArrow => input::KeyCode::Right,
+        KeyCode::Function(n) => input::KeyCode::F(n),
+        _ => return None,
+    })
+}
+
+fn modifiers(modifiers: Modifiers) -> input::Modifiers {
+    [
+        (Modifiers::SHIFT, input::Modifiers::SHIFT),
+        (Modifiers::CTRL, input::Modifiers::CONTROL),
+        (Modifiers::ALT, input::Modifiers::ALT),
+        (Modifiers::SUPER, input::Modifiers::SUPER),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| modifiers.contains(*modifier))
+    .fold(input::Modifiers::NONE, |all, (_, modifier)| all | modifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Event as _;
+    use input::{KeyCode as Code, Modifiers as Mods};
+    use ratatui::termwiz::input::{KeyEvent, MouseEvent};
+
+    #[test]
+    fn keys() {
+        let cases = [
+            (
+                KeyCode::Char('a'),
+                Modifiers::NONE,
+                Some((Code::Char('a'), Mods::NONE)),
+            ),
+            (
+                KeyCode::Char('A'),
+                Modifiers::SHIFT,
+                Some((Code::Char('A'), Mods::SHIFT)),
+            ),
+            (
+                KeyCode::Char('c'),
+                Modifiers::CTRL | Modifiers::ALT,
+                Some((Code::Char('c'), Mods::CONTROL | Mods::ALT)),
+            ),
+            (KeyCode::Tab, Modifiers::NONE, Some((Code::Tab, Mods::NONE))),
+            (
+                KeyCode::Tab,
+                Modifiers::SHIFT,
+                Some((Code::BackTab, Mods::SHIFT)),
+            ),
+            (
+                KeyCode::Enter,
+                Modifiers::NONE,
+                Some((Code::Enter, Mods::NONE)),
+            ),
+            (
+                KeyCode::Escape,
+                Modifiers::NONE,
+                Some((Code::Esc, Mods::NONE)),
+            ),
+            (
+                KeyCode::Backspace,
+                Modifiers::NONE,
+                Some((Code::Backspace, Mods::NONE)),
+            ),
+            (
+                KeyCode::Delete,
+                Modifiers::NONE,
+                Some((Code::Delete, Mods::NONE)),
+            ),
+            (
+                KeyCode::Insert,
+                Modifiers::NONE,
+                Some((Code::Insert, Mods::NONE)),
+            ),
+            (
+                KeyCode::Home,
+                Modifiers::NONE,
+                Some((Code::Home, Mods::NONE)),
+            ),
+            (
+                KeyCode::KeyPadHome,
+                Modifiers::NONE,
+                Some((Code::Home, Mods::NONE)),
+            ),
+            (KeyCode::End, Modifiers::NONE, Some((Code::End, Mods::NONE))),
+            (
+                KeyCode::KeyPadEnd,
+                Modifiers::NONE,
+                Some((Code::End, Mods::NONE)),
+            ),
+            (
+                KeyCode::PageUp,
+                Modifiers::NONE,
+                Some((Code::PageUp, Mods::NONE)),
+            ),
+            (
+                KeyCode::KeyPadPageUp,
+                Modifiers::NONE,
+                Some((Code::PageUp, Mods::NONE)),
+            ),
+            (
+                KeyCode::PageDown,
+                Modifiers::NONE,
+                Some((Code::PageDown, Mods::NONE)),
+            ),
+            (
+                KeyCode::KeyPadPageDown,
+                Modifiers::NONE,
+                Some((Code::PageDown, Mods::NONE)),
+            ),
+            (
+                KeyCode::UpArrow,
+                Modifiers::SUPER,
+                Some((Code::Up, Mods::SUPER)),
+            ),
+            (
+                KeyCode::ApplicationUpArrow,
+                Modifiers::NONE,
+                Some((Code::Up, Mods::NONE)),
+            ),
+            (
+                KeyCode::DownArrow,
+                Modifiers::NONE,
+                Some((Code::Down, Mods::NONE)),
+            ),
+            (
+                KeyCode::ApplicationDownArrow,
+                Modifiers::NONE,
+                Some((Code::Down, Mods::NONE)),
+            ),
+            (
+                KeyCode::LeftArrow,
+                Modifiers::NONE,
+                Some((Code::Left, Mods::NONE)),
+            ),
+            (
+                KeyCode::ApplicationLeftArrow,
+                Modifiers::NONE,
+                Some((Code::Left, Mods::NONE)),
+            ),
+            (
+                KeyCode::RightArrow,
+                Modifiers::NONE,
+                Some((Code::Right, Mods::NONE)),
+            ),
+            (
+                KeyCode::ApplicationRightArrow,
+                Modifiers::NONE,
+                Some((Code::Right, Mods::NONE)),
+            ),
+            (
+                KeyCode::Function(3),
+                Modifiers::NONE,
+                Some((Code::F(3), Mods::NONE)),
+            ),
+            (KeyCode::CapsLock, Modifiers::NONE, None),
+            (KeyCode::Hyper, Modifiers::NONE, None),
+        ];
+        for (key, modifiers, expected) in cases {
+            let event = InputEvent::Key(KeyEvent { key, modifiers });
+            let expected =
+                expected.map(|(code, mods)| input::Event::Key(input::Key::new(code, mods)));
+            assert_eq!(event.to_input(), expected, "{event:?}");
+        }
+    }
+
+    #[test]
+    fn mouse_events_are_zero_based() {
+        use input::{MouseButton as Button, MouseKind as Kind};
+        let cases = [
+            (MouseButtons::LEFT, Kind::Down(Button::Left)),
+            (MouseButtons::RIGHT, Kind::Down(Button::Right)),
+            (MouseButtons::MIDDLE, Kind::Down(Button::Middle)),
+            (MouseButtons::NONE, Kind::Moved),
+            (
+                MouseButtons::VERT_WHEEL | MouseButtons::WHEEL_POSITIVE,
+                Kind::ScrollUp,
+            ),
+            (MouseButtons::VERT_WHEEL, Kind::ScrollDown),
+            (
+                MouseButtons::HORZ_WHEEL | MouseButtons::WHEEL_POSITIVE,
+                Kind::ScrollLeft,
+            ),
+            (MouseButtons::HORZ_WHEEL, Kind::ScrollRight),
+        ];
+        for (mouse_buttons, kind) in cases {
+            let event = InputEvent::Mouse(MouseEvent {
+                x: 4,
+                y: 8,
+                mouse_buttons,
+                modifiers: Modifiers::CTRL,
+            });
+            let expected = input::Mouse {
+                kind,
+                column: 3,
+                row: 7,
+                modifiers: Mods::CONTROL,
+            };
+            assert_eq!(
+                event.to_input(),
+                Some(input::Event::Mouse(expected)),
+                "{event:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn held_buttons_tell_presses_drags_and_releases_apart() {
+        use input::{MouseButton as Button, MouseKind as Kind};
+        let cases = [
+            (MouseButtons::NONE, Kind::Moved),
+            (MouseButtons::LEFT, Kind::Down(Button::Left)),
+            (MouseButtons::LEFT, Kind::Drag(Some(Button::Left))),
+            (MouseButtons::VERT_WHEEL, Kind::ScrollDown),
+            (
+                MouseButtons::LEFT | MouseButtons::RIGHT,
+                Kind::Down(Button::Right),
+            ),
+            (
+                MouseButtons::LEFT | MouseButtons::RIGHT,
+                Kind::Drag(Some(Button::Left)),
+            ),
+            (MouseButtons::RIGHT, Kind::Up(Some(Button::Left))),
+            (MouseButtons::RIGHT, Kind::Drag(Some(Button::Right))),
+            (MouseButtons::NONE, Kind::Up(Some(Button::Right))),
+            (MouseButtons::NONE, Kind::Moved),
+        ];
+        let mut held = MouseButtons::default();
+        for (mouse_buttons, kind) in cases {
+            let event = InputEvent::Mouse(MouseEvent {
+                x: 1,
+                y: 1,
+                mouse_buttons,
+                modifiers: Modifiers::NONE,
+            });
+            let expected = input::Mouse {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: Mods::NONE,
+            };
+            assert_eq!(
+                event.to_input_with(&mut held),
+                Some(input::Event::Mouse(expected)),
+                "{event:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_moves_and_drags_coalesce() {
+        let mouse = |mouse_buttons| {
+            InputEvent::Mouse(MouseEvent {
+                x: 4,
+                y: 8,
+                mouse_buttons,
+                modifiers: Modifiers::NONE,
+            })
+        };
+        let cases = [
+            (MouseButtons::NONE, MouseButtons::NONE, true),
+            (MouseButtons::LEFT, MouseButtons::LEFT, true),
+            (MouseButtons::LEFT, MouseButtons::RIGHT, false),
+            (MouseButtons::VERT_WHEEL, MouseButtons::VERT_WHEEL, false),
+            (
+                MouseButtons::VERT_WHEEL | MouseButtons::WHEEL_POSITIVE,
+                MouseButtons::VERT_WHEEL | MouseButtons::WHEEL_POSITIVE,
+                false,
+            ),
+            (MouseButtons::HORZ_WHEEL, MouseButtons::HORZ_WHEEL, false),
+        ];
+        for (a, b, coalesces) in cases {
+            let (a, b) = (mouse(a), mouse(b));
+            assert_eq!(a.coalesces_with(&b), coalesces, "{a:?} then {b:?}");
+        }
+    }
+
+    #[test]
+    fn other_events() {
+        let cases = [
+            (
+                InputEvent::Paste("text".into()),
+                Some(input::Event::Paste(input::Paste("text".into()))),
+            ),
+            (
+                InputEvent::Resized { cols: 80, rows: 24 },
+                Some(input::Event::Resize(80, 24)),
+            ),
+            (InputEvent::Wake, None),
+        ];
+        for (event, expected) in cases {
+            assert_eq!(event.to_input(), expected, "{event:?}");
+        }
     }
 }
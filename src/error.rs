@@ -0,0 +1,51 @@
+use std::{any::Any, fmt, io};
+
+/// An error that keeps an application from running, or that stops it while it runs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The async executor couldn't be built.
+    Executor(io::Error),
+    /// The terminal couldn't be set up, as when the process has no terminal at all.
+    Init(io::Error),
+    /// The backend failed to read an event from the terminal.
+    EventStream(Box<dyn std::error::Error + Send + Sync>),
+    /// The interface couldn't be rendered to the terminal.
+    Draw(io::Error),
+    /// A [`Task::Perform`](crate::Task::Perform) future panicked. Contains the panic message, if
+    /// it had one.
+    TaskPanic(Option<String>),
+}
+
+impl Error {
+    pub(crate) fn task_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => Some(*message),
+            Err(payload) => payload.downcast_ref::<&str>().map(|s| s.to_string()),
+        };
+        Self::TaskPanic(message)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Executor(e) => write!(f, "failed to build executor: {e}"),
+            Self::Init(e) => write!(f, "failed to initialize terminal: {e}"),
+            Self::EventStream(e) => write!(f, "failed to read terminal events: {e}"),
+            Self::Draw(e) => write!(f, "failed to draw to terminal: {e}"),
+            Self::TaskPanic(Some(message)) => write!(f, "a task panicked: {message}"),
+            Self::TaskPanic(None) => f.write_str("a task panicked"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Executor(e) | Self::Init(e) | Self::Draw(e) => Some(e),
+            Self::EventStream(e) => Some(&**e),
+            Self::TaskPanic(_) => None,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::process::{ExitCode, Termination};
+
+/// How an application stopped running, returned by [`App::run`](crate::App::run).
+///
+/// The state the application stopped with is still available through
+/// [`App::state`](crate::App::state) or [`App::into_state`](crate::App::into_state).
+///
+/// This can be returned from `main` directly: [`Exit::Value`] reports its value, [`Exit::Quit`]
+/// reports success, and [`Exit::InputClosed`] reports failure. The same goes for converting it
+/// into an [`ExitCode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit<T> {
+    /// The application exited with a value, through
+    /// [`Outcome::quit_with`](crate::Outcome::quit_with).
+    Value(T),
+    /// The application quit without a value, through [`Task::Quit`](crate::Task::Quit),
+    /// [`Outcome::quit`](crate::Outcome::quit) or an [`AppHandle`](crate::AppHandle).
+    Quit,
+    /// The terminal stopped producing events, as when its input was closed.
+    InputClosed,
+}
+
+impl<T> Exit<T> {
+    /// Get the value the application exited with, if any.
+    pub fn value(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Quit | Self::InputClosed => None,
+        }
+    }
+
+    /// Transform the value the application exited with, if any.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Exit<U> {
+        match self {
+            Self::Value(value) => Exit::Value(f(value)),
+            Self::Quit => Exit::Quit,
+            Self::InputClosed => Exit::InputClosed,
+        }
+    }
+}
+
+impl<T: Into<ExitCode>> From<Exit<T>> for ExitCode {
+    fn from(exit: Exit<T>) -> Self {
+        match exit {
+            Exit::Value(value) => value.into(),
+            Exit::Quit => ExitCode::SUCCESS,
+            Exit::InputClosed => ExitCode::FAILURE,
+        }
+    }
+}
+
+impl<T: Termination> Termination for Exit<T> {
+    fn report(self) -> ExitCode {
+        match self {
+            Self::Value(value) => value.report(),
+            Self::Quit => ExitCode::SUCCESS,
+            Self::InputClosed => ExitCode::FAILURE,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::backend::TerminalOptions;
+use std::{
+    cell::Cell,
+    panic,
+    sync::{
+        Mutex, Once, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Every terminal currently set up by a running application, along with how to restore it.
+static GUARDED: Mutex<Vec<Guarded>> = Mutex::new(Vec::new());
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// The terminal of the application this thread runs or renders for, if any.
+    static CURRENT: Cell<Option<u64>> = const { Cell::new(None) };
+    /// How many futures this thread is polling whose panics are caught and reported as errors,
+    /// such as tasks.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Guarded {
+    id: u64,
+    restore: fn(&TerminalOptions),
+    options: TerminalOptions,
+}
+
+/// Restores the terminal when dropped, or as soon as the application panics, whichever comes
+/// first.
+///
+/// Held by [`App::run`](crate::App::run) for as long as the terminal is set up. A single panic
+/// hook is installed for the whole process the first time a terminal is guarded, on top of the
+/// hook that was installed before it. When a thread panics, it restores the terminal of the
+/// application that thread runs or renders for, if any, then runs the previous hook, so that the
+/// panic message is printed to a usable terminal. Panics that are caught and reported as errors,
+/// such as those of tasks, leave every terminal as it is and skip the previous hook, as their
+/// message is part of the error.
+pub(crate) struct TerminalGuard {
+    id: u64,
+    /// The terminal guarded for this thread before this one, if any.
+    previous: Option<u64>,
+}
+
+impl TerminalGuard {
+    /// Guard a terminal that was set up with `options`, restoring it with `restore`.
+    pub(crate) fn new(restore: fn(&TerminalOptions), options: TerminalOptions) -> Self {
+        INSTALL_HOOK.call_once(|| {
+            let previous_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                // The default hook would print over the interface.
+                if is_catching() {
+                    return;
+                }
+                restore_current();
+                previous_hook(info);
+            }));
+        });
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        guarded().push(Guarded {
+            id,
+            restore,
+            options,
+        });
+        Self {
+            id,
+            previous: CURRENT.replace(Some(id)),
+        }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        CURRENT.set(self.previous);
+        // The panic hook may have restored the terminal already.
+        restore(self.id);
+    }
+}
+
+fn guarded() -> std::sync::MutexGuard<'static, Vec<Guarded>> {
+    GUARDED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Restore the terminal guarded as `id`, unless it has been restored already.
+fn restore(id: u64) {
+    let guarded = {
+        let mut all = guarded();
+        let index = all.iter().position(|guarded| guarded.id == id);
+        index.map(|index| all.swap_remove(index))
+    };
+    if let Some(guarded) = guarded {
+        (guarded.restore)(&guarded.options);
+    }
+}
+
+/// Whether the current thread is panicking within [`catching`].
+fn is_catching() -> bool {
+    CATCHING.try_with(Cell::get).unwrap_or(0) > 0
+}
+
+/// Restore the terminal of the application the current thread runs or renders for.
+fn restore_current() {
+    if let Some(id) = CURRENT.try_with(Cell::get).ok().flatten() {
+        restore(id);
+    }
+}
+
+/// The terminal guarded for the current thread, to hand to [`enter`] on a thread that renders
+/// for the same application.
+pub(crate) fn current() -> Option<u64> {
+    CURRENT.get()
+}
+
+/// Restore the terminal guarded as `id` (from [`current`]) if the current thread panics.
+pub(crate) fn enter(id: Option<u64>) {
+    CURRENT.set(id);
+}
+
+/// Call `f`, whose panics are caught and reported as errors rather than restoring the terminal and
+/// printing their message.
+pub(crate) fn catching<T>(f: impl FnOnce() -> T) -> T {
+    struct Catching;
+
+    impl Drop for Catching {
+        fn drop(&mut self) {
+            CATCHING.set(CATCHING.get() - 1);
+        }
+    }
+
+    CATCHING.set(CATCHING.get() + 1);
+    let _catching = Catching;
+    f()
+}
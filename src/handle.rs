@@ -0,0 +1,133 @@
+use futures::task::AtomicWaker;
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+/// State shared between an [`App`](crate::App) and all of its [`AppHandle`]s.
+#[derive(Default)]
+pub(crate) struct Control {
+    running: AtomicBool,
+    quit: AtomicBool,
+    /// The error of the first task that panicked, if any.
+    panic: Mutex<Option<crate::Error>>,
+    waker: AtomicWaker,
+}
+
+impl Control {
+    /// Mark the application as running until the returned guard is dropped, even if the run is
+    /// cancelled.
+    pub(crate) fn run(self: &Arc<Self>) -> Running {
+        self.set_running(true);
+        Running(self.clone())
+    }
+
+    fn set_running(&self, running: bool) {
+        self.running.store(running, Ordering::SeqCst);
+        if !running {
+            self.quit.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Resolves once a quit has been requested through a handle.
+    pub(crate) fn poll_quit(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.waker.register(cx.waker());
+        if self.quit.swap(false, Ordering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Stop the application because a task panicked.
+    pub(crate) fn task_panicked(&self, error: crate::Error) {
+        self.panic.lock().unwrap().get_or_insert(error);
+        self.waker.wake();
+    }
+
+    /// Resolves with the error of a task that panicked.
+    pub(crate) fn poll_panic(&self, cx: &mut Context<'_>) -> Poll<crate::Error> {
+        self.waker.register(cx.waker());
+        match self.panic.lock().unwrap().take() {
+            Some(error) => Poll::Ready(error),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Marks the application as no longer running when dropped.
+pub(crate) struct Running(Arc<Control>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.set_running(false);
+    }
+}
+
+/// A cloneable, thread-safe handle for interacting with an [`App`](crate::App) from outside of
+/// it.
+///
+/// Obtained via [`App::handle`](crate::App::handle). Messages sent before the application starts
+/// running are queued and delivered once it does. Once the application has been dropped, every
+/// method that would affect it returns a [`SendError`].
+pub struct AppHandle<M> {
+    send: Arc<dyn Fn(M) -> bool + Send + Sync>,
+    control: Arc<Control>,
+}
+
+impl<M> AppHandle<M> {
+    pub(crate) fn new(send: Arc<dyn Fn(M) -> bool + Send + Sync>, control: Arc<Control>) -> Self {
+        Self { send, control }
+    }
+
+    /// Send a message to the application, as if it had been produced by a task.
+    pub fn send(&self, message: M) -> Result<(), SendError> {
+        if (self.send)(message) {
+            Ok(())
+        } else {
+            Err(SendError)
+        }
+    }
+
+    /// Ask the application to quit, as if [`Task::Quit`](crate::Task::Quit) had been returned.
+    ///
+    /// Returns an error if the application is not currently running.
+    pub fn quit(&self) -> Result<(), SendError> {
+        if !self.is_running() {
+            return Err(SendError);
+        }
+        self.control.quit.store(true, Ordering::SeqCst);
+        self.control.waker.wake();
+        Ok(())
+    }
+
+    /// Whether the application's main loop is currently running.
+    pub fn is_running(&self) -> bool {
+        self.control.running.load(Ordering::SeqCst)
+    }
+}
+
+impl<M> Clone for AppHandle<M> {
+    fn clone(&self) -> Self {
+        Self {
+            send: self.send.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+/// The error returned by [`AppHandle`] when the application can no longer be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError;
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the application is not running")
+    }
+}
+
+impl std::error::Error for SendError {}
@@ -0,0 +1,79 @@
+use futures::{
+    Stream, StreamExt,
+    stream::{BoxStream, FusedStream, SelectAll},
+};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The messages an application receives, both through its channel (from tasks and
+/// [`AppHandle`](crate::AppHandle)s) and from its subscriptions.
+///
+/// Whenever both are ready, the two take turns, so that a steady flow of messages from one of
+/// them never holds back the other.
+pub(crate) struct Inbox<Rx, M> {
+    rx: Rx,
+    subscriptions: SelectAll<BoxStream<'static, M>>,
+    /// Whether subscriptions are polled before the channel next time.
+    subscriptions_first: bool,
+}
+
+impl<Rx, M> Inbox<Rx, M> {
+    pub(crate) fn new(rx: Rx) -> Self {
+        Self {
+            rx,
+            subscriptions: SelectAll::new(),
+            subscriptions_first: false,
+        }
+    }
+
+    pub(crate) fn subscribe(&mut self, subscription: BoxStream<'static, M>) {
+        self.subscriptions.push(subscription);
+    }
+}
+
+/// Ends when the channel does. Running out of subscriptions doesn't end it.
+impl<Rx: Stream<Item = M> + Unpin, M> Stream for Inbox<Rx, M> {
+    type Item = M;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<M>> {
+        let this = &mut *self;
+        for _ in 0..2 {
+            let polled = if this.subscriptions_first {
+                match this.subscriptions.poll_next_unpin(cx) {
+                    Poll::Ready(None) => Poll::Pending,
+                    polled => polled,
+                }
+            } else {
+                this.rx.poll_next_unpin(cx)
+            };
+            // Whichever source produced a message goes last next time.
+            this.subscriptions_first = !this.subscriptions_first;
+            if polled.is_ready() {
+                return polled;
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<Rx: FusedStream<Item = M> + Unpin, M> FusedStream for Inbox<Rx, M> {
+    fn is_terminated(&self) -> bool {
+        self.rx.is_terminated()
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn sources_take_turns() {
+        let mut inbox = Inbox::new(stream::iter([1, 2, 3, 4]));
+        inbox.subscribe(stream::iter([10, 20, 30]).boxed());
+        let messages: Vec<_> = inbox.collect().await;
+        assert_eq!(messages, [1, 10, 2, 20, 3, 30, 4]);
+    }
+}
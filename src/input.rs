@@ -0,0 +1,238 @@
+//! Terminal events in the same form for every backend.
+//!
+//! This is the event type of [`DynBackend`](crate::backend::DynBackend), so that update functions
+//! are written once whichever backend is chosen at runtime. With any other backend, its events
+//! are converted with [`Event::to_input`](crate::backend::Event::to_input), and
+//! [`App::on_input`](crate::App::on_input) turns them into messages in this form.
+//!
+//! Backends differ in what they can report. termion can't report pasted text or focus changes,
+//! only crossterm reports key repeats and releases, and not every backend tells which mouse
+//! button was released or dragged. Keys a backend reports that have no equivalent here, such as
+//! media keys, are left out.
+
+use std::{
+    fmt,
+    ops::{BitOr, BitOrAssign},
+};
+
+/// A terminal event, in the same form whichever backend produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// A key was pressed, repeated or released.
+    Key(Key),
+    /// The mouse was used, if [mouse reporting](crate::backend::TerminalOptions::mouse) is
+    /// enabled.
+    Mouse(Mouse),
+    /// Text was pasted, if [bracketed paste](crate::backend::TerminalOptions::paste) is enabled.
+    Paste(Paste),
+    /// The terminal gained or lost focus, if [focus
+    /// reporting](crate::backend::TerminalOptions::focus) is enabled.
+    Focus(Focus),
+    /// The terminal was resized to the given width and height.
+    Resize(u16, u16),
+}
+
+/// A key event.
+///
+/// Characters are reported as typed, so whether <kbd>Shift</kbd> is included in the modifiers of
+/// an uppercase character depends on the backend. <kbd>Shift</kbd>+<kbd>Tab</kbd> is always
+/// reported as [`KeyCode::BackTab`], with [`Modifiers::SHIFT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+    pub kind: KeyKind,
+}
+
+impl Key {
+    /// A key press.
+    pub const fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self {
+            code,
+            modifiers,
+            kind: KeyKind::Press,
+        }
+    }
+}
+
+/// Which key an event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyCode {
+    /// A character, including a space.
+    Char(char),
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    /// <kbd>Shift</kbd>+<kbd>Tab</kbd>.
+    BackTab,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A function key, such as `F(1)` for <kbd>F1</kbd>.
+    F(u8),
+}
+
+/// Whether a key was pressed, repeated or released.
+///
+/// Only crossterm with [keyboard
+/// enhancement](crate::backend::TerminalOptions::keyboard_enhancement) reports anything but
+/// presses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum KeyKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// The modifier keys held during an event. Combine them with `|`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1 << 0);
+    pub const CONTROL: Self = Self(1 << 1);
+    pub const ALT: Self = Self(1 << 2);
+    pub const SUPER: Self = Self(1 << 3);
+
+    const NAMES: [(Self, &str); 4] = [
+        (Self::SHIFT, "SHIFT"),
+        (Self::CONTROL, "CONTROL"),
+        (Self::ALT, "ALT"),
+        (Self::SUPER, "SUPER"),
+    ];
+
+    /// Check if every modifier in `other` is held.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check if no modifier is held.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("Modifiers(NONE)");
+        }
+        f.write_str("Modifiers(")?;
+        let mut names = Self::NAMES
+            .iter()
+            .filter(|(modifier, _)| self.contains(*modifier))
+            .map(|(_, name)| name);
+        if let Some(name) = names.next() {
+            f.write_str(name)?;
+        }
+        for name in names {
+            write!(f, " | {name}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// A mouse event, at a zero-based column and row of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mouse {
+    pub kind: MouseKind,
+    pub column: u16,
+    pub row: u16,
+    pub modifiers: Modifiers,
+}
+
+/// What the mouse did.
+///
+/// termwiz only reports which buttons are held, so presses, drags and releases are told apart
+/// from the buttons held before each event. Converting a lone termwiz event with
+/// [`Event::to_input`](crate::backend::Event::to_input) reports any held button as
+/// [`MouseKind::Down`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseKind {
+    Down(MouseButton),
+    /// A button was released. termion doesn't report which.
+    Up(Option<MouseButton>),
+    /// The mouse moved with a button held. termion doesn't report which.
+    Drag(Option<MouseButton>),
+    /// The mouse moved without a button held.
+    Moved,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// Pasted text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Paste(pub String);
+
+/// A change of the terminal's focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Focus {
+    Gained,
+    Lost,
+}
+
+impl crate::backend::Event for Event {
+    type InputState = ();
+
+    fn resize(&self) -> Option<(u16, u16)> {
+        if let Event::Resize(width, height) = self {
+            Some((*width, *height))
+        } else {
+            None
+        }
+    }
+
+    fn coalesces_with(&self, next: &Self) -> bool {
+        match (self, next) {
+            (Event::Resize(..), Event::Resize(..)) => true,
+            (Event::Mouse(a), Event::Mouse(b)) => {
+                a.modifiers == b.modifiers
+                    && match (a.kind, b.kind) {
+                        (MouseKind::Moved, MouseKind::Moved) => true,
+                        (MouseKind::Drag(a), MouseKind::Drag(b)) => a == b,
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+
+    fn to_input(&self) -> Option<Event> {
+        Some(self.clone())
+    }
+}
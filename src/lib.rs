@@ -11,8 +11,26 @@
 //! <div class="warning">
 //! This framework provides a built-in subscription to terminal events. <strong>Do not manually
 //! subscribe to events</strong>, as this will cause the two subscriptions to fight over each event.
+//! Use [`App::on_event`] (or [`App::on_input`]) to turn terminal events into your own messages
+//! instead.
 //! </div>
 //!
+//! # Scheduling
+//!
+//! The main loop waits until something is ready, then applies every update that is already
+//! ready (up to the [batch budget](App::batch_budget)) before rendering at most once. Whenever
+//! several sources are ready at the same time, they are always handled in this order:
+//!
+//! 1. Quit requests made through an [`AppHandle`], and tasks that panicked.
+//! 2. Terminal events.
+//! 3. A frame that was deferred by [`App::max_fps`] (only between batches).
+//! 4. Messages produced by tasks or sent through an [`AppHandle`], and items from subscriptions.
+//!    When both are ready, the two take turns.
+//!
+//! This means keypresses are never stuck behind a flood of background messages, and a steady
+//! flow of messages from tasks doesn't hold back subscriptions (or the other way around). Items
+//! from a single source are always applied in the order they were produced.
+//!
 //! # Features
 //!
 //! This crate works with all three officially supported ratatui backends:
@@ -23,39 +41,95 @@
 //!
 //! There is a cargo feature for each backend implementation. **These feature flags are not
 //! mutually exclusive**, though if you have only one enabled that backend will be used without
-//! manual specification.
+//! manual specification. With several enabled, the default is [`DynBackend`](backend::DynBackend),
+//! which lets the backend be chosen at runtime, and reports events in the same
+//! [form](input::Event) for all of them.
 
 pub mod backend;
+mod error;
+mod exit;
+mod guard;
+mod handle;
+mod inbox;
+pub mod input;
+mod outcome;
+mod render;
+pub mod subscription;
 
-use backend::{Backend, Event, New};
+pub use error::Error;
+pub use exit::Exit;
+pub use handle::{AppHandle, SendError};
+pub use outcome::{IntoOutcome, Outcome};
+use subscription::Subscription;
+
+use backend::{Backend, Event, New, TerminalOptions};
 use byor::{
     channel::mpsc::{RuntimeMpsc, UnboundedSender},
     executor::{Executor, Handle, RuntimeExecutor},
+    time::Time,
 };
 use cfg_if::cfg_if;
 use futures::{
-    Stream, StreamExt,
-    future::BoxFuture,
-    stream::{BoxStream, Fuse, FusedStream, SelectAll},
+    FutureExt, Stream, StreamExt,
+    future::{self, BoxFuture},
+    stream::{Fuse, FusedStream},
+};
+use outcome::Render;
+use ratatui::{Frame, Terminal, Viewport, text::Text};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::ControlFlow,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex, PoisonError},
+    task::Poll,
+    time::{Duration, Instant},
 };
-use ratatui::{Frame, Terminal};
-use std::sync::Arc;
 
 /// A trait for a struct that can update the state of the application.
 ///
-/// Returns a task to be executed along with whether the interface should be re-rendered.
+/// Returns an [`Outcome`] describing the tasks to execute and whether the interface should be
+/// re-rendered.
 ///
 /// You shouldn't need to manually implement this trait. The provided implementation should be
-/// sufficient.
+/// sufficient: update functions can return an [`Outcome`], a `(Task<M>, bool)` or a [`Task`].
 pub trait Updater<State, M, E: Event> {
-    fn update(&self, state: &mut State, update: Update<M, E>) -> (Task<M>, bool);
+    /// The value the application can quit with through [`Outcome::quit_with`].
+    type Exit;
+
+    fn update(&self, state: &mut State, update: Update<M, E>) -> Outcome<M, Self::Exit>;
 }
 
-impl<State, M, E: Event, F: Fn(&mut State, Update<M, E>) -> (Task<M>, bool)> Updater<State, M, E>
-    for F
+impl<State, M, E: Event, F, O> Updater<State, M, E> for F
+where
+    F: Fn(&mut State, Update<M, E>) -> O,
+    O: IntoOutcome<M>,
+{
+    type Exit = O::Exit;
+
+    fn update(&self, state: &mut State, update: Update<M, E>) -> Outcome<M, O::Exit> {
+        self(state, update).into_outcome()
+    }
+}
+
+/// An [`Updater`] that only ever receives messages of user-defined type.
+///
+/// Terminal events are dropped before reaching the wrapped function, so this is meant to be used
+/// alongside [`App::on_event`] or [`App::on_input`], which convert the events you care about into
+/// messages.
+pub struct Messages<F>(pub F);
+
+impl<State, M, E: Event, F, O> Updater<State, M, E> for Messages<F>
+where
+    F: Fn(&mut State, M) -> O,
+    O: IntoOutcome<M>,
 {
-    fn update(&self, state: &mut State, update: Update<M, E>) -> (Task<M>, bool) {
-        self(state, update)
+    type Exit = O::Exit;
+
+    fn update(&self, state: &mut State, update: Update<M, E>) -> Outcome<M, O::Exit> {
+        match update {
+            Update::Message(message) => (self.0)(state, message).into_outcome(),
+            Update::Terminal(_) => Outcome::none(),
+        }
     }
 }
 
@@ -98,6 +172,14 @@ cfg_if! {
             /// A message of user-defined type.
             Message(M),
         }
+    } else if #[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))] {
+        /// A message to be sent to the application.
+        pub enum Update<M, E: Event = input::Event> {
+            /// A terminal event, in the same form for every backend.
+            Terminal(E),
+            /// A message of user-defined type.
+            Message(M),
+        }
     } else {
         /// A message to be sent to the application.
         pub enum Update<M, E: Event> {
@@ -121,6 +203,9 @@ pub enum Task<T> {
     /// This simply breaks out of the runtime's main loop and allows program execution to
     /// continue to completion. It will not cancel any pending tasks.
     Quit,
+    /// Print text above an [inline viewport](App::viewport), where it stays in the terminal's
+    /// scrollback. Ignored for other viewports.
+    InsertBefore(Text<'static>),
 }
 
 impl<T> Task<T> {
@@ -128,15 +213,30 @@ impl<T> Task<T> {
     pub fn perform(future: impl Future<Output = T> + Send + 'static) -> Self {
         Task::Perform(Box::pin(future))
     }
+
+    /// Create a new task that prints `text` above an [inline viewport](App::viewport).
+    ///
+    /// Each line of `text` takes up one row of the terminal, and is cut off at its width.
+    pub fn insert_before(text: impl Into<Text<'static>>) -> Self {
+        Task::InsertBefore(text.into())
+    }
 }
 
 trait TaskFutExt<T: 'static> {
-    async fn run(self, tx: impl UnboundedSender<T>);
+    async fn run(self, tx: impl UnboundedSender<T>, control: Arc<handle::Control>);
 }
 
 impl<T: 'static, F: Future<Output = T>> TaskFutExt<T> for F {
-    async fn run(self, tx: impl UnboundedSender<T>) {
-        tx.send(self.await).unwrap();
+    async fn run(self, tx: impl UnboundedSender<T>, control: Arc<handle::Control>) {
+        let mut task = std::pin::pin!(self);
+        let task = future::poll_fn(|cx| guard::catching(|| task.as_mut().poll(cx)));
+        match AssertUnwindSafe(task).catch_unwind().await {
+            // The application may have shut down while this task was running.
+            Ok(value) => {
+                let _ = tx.send(value);
+            }
+            Err(payload) => control.task_panicked(Error::task_panic(payload)),
+        }
     }
 }
 
@@ -150,56 +250,196 @@ pub struct App<
     State = (),
 > {
     updater: U,
-    viewer: V,
+    viewer: Arc<V>,
     state: State,
-    rx: Fuse<<R as RuntimeMpsc>::UnboundedReceiver<M>>,
+    inbox: inbox::Inbox<Fuse<<R as RuntimeMpsc>::UnboundedReceiver<M>>, M>,
     tx: <R as RuntimeMpsc>::UnboundedSender<M>,
-    event_stream: B::EventStream,
-    subscriptions: SelectAll<BoxStream<'static, M>>,
-    executor: Arc<R::Executor>,
+    spawner: Spawner<R::Executor>,
+    control: Arc<handle::Control>,
+    #[allow(clippy::type_complexity)]
+    event_mapper: Option<Box<dyn Fn(&B::Event, &State) -> Option<M> + Send>>,
+    coalesce_events: bool,
+    frame_limit: Option<render::FrameLimit>,
+    batch_budget: usize,
+    #[allow(clippy::type_complexity)]
+    on_batch: Option<Box<dyn FnMut(&BatchMetrics) + Send>>,
+    #[allow(clippy::type_complexity)]
+    fingerprint: Option<Box<dyn Fn(&State) -> u64 + Send>>,
+    #[allow(clippy::type_complexity)]
+    render_thread: Option<fn(Terminal<B>, Arc<V>, Viewport) -> render::RenderThread<State>>,
+    terminal_options: TerminalOptions,
+    #[allow(clippy::type_complexity)]
+    init: Option<Box<dyn FnMut(&mut State) -> Outcome<M, U::Exit> + Send>>,
+    #[allow(clippy::type_complexity)]
+    on_idle: Option<Box<dyn FnMut(&mut State) -> Outcome<M, U::Exit> + Send>>,
+    #[allow(clippy::type_complexity)]
+    on_resize: Option<Box<dyn FnMut(&mut State, u16, u16) + Send>>,
+    #[allow(clippy::type_complexity)]
+    on_exit: Option<Box<dyn FnMut(&mut State) + Send>>,
+}
+
+/// An item of a backend's event stream.
+type EventItem<B, R> = Option<Result<<B as Backend<R>>::Event, <B as Backend<R>>::Error>>;
+
+/// The next update to apply, or why the application stopped running.
+type Next<M, E, X> = ControlFlow<Result<Exit<X>, Error>, Update<M, E>>;
+
+const DEFAULT_BATCH_BUDGET: usize = 64;
+
+/// Statistics about a batch of updates, reported through [`App::on_batch`].
+///
+/// The main loop waits for an update, then applies every other update that is already ready (up
+/// to the [batch budget](App::batch_budget)) before rendering at most once.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatchMetrics {
+    /// The number of messages applied, including those from subscriptions.
+    pub messages: usize,
+    /// The number of terminal events applied, after coalescing.
+    pub events: usize,
+    /// Whether the batch was cut short by the budget while more updates may have been ready.
+    pub budget_exhausted: bool,
+    /// The total time spent applying updates.
+    pub update_time: Duration,
+    /// The time spent rendering at the end of the batch, if it rendered.
+    ///
+    /// This is `None` if no update requested a render, or if the render was deferred by
+    /// [`App::max_fps`].
+    pub render_time: Option<Duration>,
 }
 
 /// Lets you construct an [`App`] with a custom backend in a more convenient way.
-pub struct AppWithBackend<R, B>(std::marker::PhantomData<(R, B)>);
+pub struct AppWithBackend<R: RuntimeExecutor, B> {
+    spawner: Spawner<R::Executor>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+/// How an application spawns its tasks.
+enum Spawner<E> {
+    /// On an executor of its runtime, built by the application or shared with the rest of the
+    /// program.
+    Executor(Arc<E>),
+    /// Through a function, such as one spawning onto a runtime that is already running.
+    #[allow(clippy::type_complexity)]
+    Spawn(Arc<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>),
+}
+
+impl<E> Clone for Spawner<E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Executor(executor) => Self::Executor(executor.clone()),
+            Self::Spawn(spawn) => Self::Spawn(spawn.clone()),
+        }
+    }
+}
+
+impl<E: Executor> Spawner<E> {
+    fn spawn(&self, task: impl Future<Output = ()> + Send + 'static) {
+        match self {
+            Self::Executor(executor) => executor.spawn(task).detach(),
+            Self::Spawn(spawn) => spawn(Box::pin(task)),
+        }
+    }
+
+    /// Run `future` to completion on the current thread.
+    fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+        match self {
+            Self::Executor(executor) => executor.block_on(future),
+            Self::Spawn(_) => futures::executor::block_on(future),
+        }
+    }
+}
 
 impl<R: RuntimeExecutor + RuntimeMpsc, B: Backend<R>> AppWithBackend<R, B> {
     #[allow(clippy::new_ret_no_self)]
     /// Create a new application with default initial state.
+    ///
+    /// Fails if the runtime's executor can't be built.
+    ///
+    /// This builds a runtime of the application's own. Within a program whose runtime is already
+    /// running, use [`AppWithBackend::with_spawner`] instead.
     pub fn new<State: Default, M, U: Updater<State, M, B::Event>, V: Viewer<State>>(
         update: U,
         view: V,
-    ) -> App<M, U, V, B, R, State> {
-        let (tx, rx) = R::unbounded_channel();
-        let executor = Arc::new(R::Executor::new().expect("Failed to build executor"));
-        App {
-            updater: update,
-            viewer: view,
-            state: State::default(),
-            tx,
-            rx: rx.fuse(),
-            event_stream: B::EventStream::new(),
-            subscriptions: SelectAll::new(),
-            executor,
-        }
+    ) -> Result<App<M, U, V, B, R, State>, Error> {
+        Self::new_with(State::default(), update, view)
     }
 
     /// Create a new application with a custom initial state.
+    ///
+    /// Fails if the runtime's executor can't be built.
     pub fn new_with<State, M, U: Updater<State, M, B::Event>, V: Viewer<State>>(
         state: State,
         update: U,
         view: V,
+    ) -> Result<App<M, U, V, B, R, State>, Error> {
+        let executor = R::Executor::new().map_err(Error::Executor)?;
+        Ok(Self::with_executor(Arc::new(executor)).build_with(state, update, view))
+    }
+
+    /// Run tasks on `executor` instead of building a new one, such as to share a runtime with the
+    /// rest of a program.
+    ///
+    /// Build the application with [`AppWithBackend::build`] or [`AppWithBackend::build_with`].
+    pub fn with_executor(executor: Arc<R::Executor>) -> Self {
+        Self {
+            spawner: Spawner::Executor(executor),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Run tasks by handing them to `spawn` instead of on an executor of the application's own.
+    ///
+    /// This embeds an application in a program whose runtime is already running, as under
+    /// `#[tokio::main]`: spawn tasks onto that runtime, with `|task| { tokio::spawn(task); }` or
+    /// through a runtime handle, and run the application with [`App::run_async`]. Unlike with
+    /// [`AppWithBackend::new`], no runtime is built, so none is dropped within that runtime
+    /// either, which tokio doesn't allow.
+    ///
+    /// Build the application with [`AppWithBackend::build`] or [`AppWithBackend::build_with`].
+    pub fn with_spawner(spawn: impl Fn(BoxFuture<'static, ()>) + Send + Sync + 'static) -> Self {
+        Self {
+            spawner: Spawner::Spawn(Arc::new(spawn)),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Build an application with default initial state.
+    pub fn build<State: Default, M, U: Updater<State, M, B::Event>, V: Viewer<State>>(
+        self,
+        update: U,
+        view: V,
+    ) -> App<M, U, V, B, R, State> {
+        self.build_with(State::default(), update, view)
+    }
+
+    /// Build an application with a custom initial state.
+    pub fn build_with<State, M, U: Updater<State, M, B::Event>, V: Viewer<State>>(
+        self,
+        state: State,
+        update: U,
+        view: V,
     ) -> App<M, U, V, B, R, State> {
         let (tx, rx) = R::unbounded_channel();
-        let executor = Arc::new(R::Executor::new().expect("Failed to build executor"));
         App {
             updater: update,
-            viewer: view,
+            viewer: Arc::new(view),
             state,
             tx,
-            rx: rx.fuse(),
-            event_stream: B::EventStream::new(),
-            subscriptions: SelectAll::new(),
-            executor,
+            inbox: inbox::Inbox::new(rx.fuse()),
+            spawner: self.spawner,
+            control: Arc::default(),
+            event_mapper: None,
+            coalesce_events: false,
+            frame_limit: None,
+            batch_budget: DEFAULT_BATCH_BUDGET,
+            on_batch: None,
+            fingerprint: None,
+            render_thread: None,
+            terminal_options: TerminalOptions::default(),
+            init: None,
+            on_idle: None,
+            on_resize: None,
+            on_exit: None,
         }
     }
 }
@@ -224,26 +464,30 @@ cfg_if! {
 cfg_if! {
     if #[cfg(all(feature = "crossterm", not(feature = "termwiz"), not(feature = "termion")))] {
         pub type DefaultBackend = backend::CrosstermBackend;
-        type DefaultEvent = ratatui::crossterm::event::Event;
     } else if #[cfg(all(feature = "termwiz", not(feature = "crossterm"), not(feature = "termion")))] {
-        pub type DefaultBackend = ratatui::backend::TermwizBackend;
-        type DefaultEvent = ratatui::termwiz::input::InputEvent;
+        pub type DefaultBackend = backend::TermwizBackend;
     } else if #[cfg(all(feature = "termion", not(feature = "crossterm"), not(feature = "termwiz")))] {
         pub type DefaultBackend = backend::TermionBackend;
-        type DefaultEvent = backend::termion::Event;
+    } else if #[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))] {
+        pub type DefaultBackend = backend::DynBackend;
     }
 }
 
 cfg_if! {
     if #[cfg(all(
             any(feature = "tokio", feature = "smol", feature = "futures"),
-            not(all(feature = "tokio", feature = "smol", feature = "futures")),
+            not(any(
+                all(feature = "tokio", feature = "smol"),
+                all(feature = "tokio", feature = "futures"),
+                all(feature = "smol", feature = "futures"),
+            )),
             any(feature = "crossterm", feature = "termion", feature = "termwiz"),
-            not(all(feature = "crossterm", feature = "termion", feature = "termwiz"))
         ))] {
-        impl<State, M, U: Updater<State, M, DefaultEvent>, V: Viewer<State>> App<M, U, V, DefaultBackend, DefaultRuntime, State> {
+        impl<State, M, U: Updater<State, M, <DefaultBackend as Backend<DefaultRuntime>>::Event>, V: Viewer<State>> App<M, U, V, DefaultBackend, DefaultRuntime, State> {
             /// Create a new application with default initial state.
-            pub fn new(update: U, view: V) -> Self
+            ///
+            /// Fails if the runtime's executor can't be built.
+            pub fn new(update: U, view: V) -> Result<Self, Error>
             where
                 State: Default,
             {
@@ -251,13 +495,40 @@ cfg_if! {
             }
 
             /// Create a new application with a custom initial state.
-            pub fn new_with(state: State, update: U, view: V) -> Self {
+            ///
+            /// Fails if the runtime's executor can't be built.
+            pub fn new_with(state: State, update: U, view: V) -> Result<Self, Error> {
                 AppWithBackend::<DefaultRuntime, DefaultBackend>::new_with(state, update, view)
             }
         }
     }
 }
 
+#[cfg(any(feature = "crossterm", feature = "termion", feature = "termwiz"))]
+impl<
+    State,
+    M,
+    U: Updater<State, M, input::Event>,
+    V: Viewer<State>,
+    R: RuntimeExecutor + RuntimeMpsc,
+> App<M, U, V, backend::DynBackend, R, State>
+where
+    backend::DynBackend: Backend<R, Event = input::Event>,
+{
+    /// Choose the backend the application runs with, among those whose cargo feature is enabled.
+    ///
+    /// Defaults to [`BackendKind::default`](backend::BackendKind::default). A
+    /// [`BackendKind`](backend::BackendKind) can be parsed from a backend's name, to choose it
+    /// from an environment variable or a command line flag.
+    ///
+    /// This is stored in [`TerminalOptions::backend`], so it is reset by a later call to
+    /// [`App::terminal_options`].
+    pub fn backend(mut self, kind: backend::BackendKind) -> Self {
+        self.terminal_options.backend = Some(kind);
+        self
+    }
+}
+
 impl<
     State,
     M,
@@ -272,65 +543,649 @@ where
     <B as Backend<R>>::EventStream: FusedStream,
 {
     /// Add a subscription to the application.
+    ///
+    /// The stream is polled on the application's main loop, so it should only wait for things to
+    /// happen. Anything that blocks or computes for a while holds back input and rendering, and
+    /// belongs in a [`Task::perform`] or on a thread of its own, as with
+    /// [`stdin_lines`](subscription::stdin_lines).
     pub fn subscription(mut self, subscription: impl Stream<Item = M> + Send + 'static) -> Self {
-        self.subscriptions.push(Box::pin(subscription));
+        self.inbox.subscribe(Box::pin(subscription));
+        self
+    }
+
+    /// Add a [`Subscription`] that can notify the application when it ends and be restarted.
+    ///
+    /// Like with [`App::subscription`], its streams are polled on the application's main loop.
+    pub fn subscribe(mut self, subscription: Subscription<M>) -> Self
+    where
+        R: subscription::Timer + 'static,
+    {
+        self.inbox.subscribe(subscription.into_stream::<R>());
+        self
+    }
+
+    /// Convert terminal events into messages before they reach the updater.
+    ///
+    /// Once set, [`Update::Terminal`] is never passed to the updater: events for which `f` returns
+    /// `Some` are delivered as [`Update::Message`], and all others are dropped. Resize events are
+    /// still handled (and trigger a re-render) regardless of what `f` returns.
+    ///
+    /// Combine this with [`Messages`] to write an update function that only ever sees `M`.
+    pub fn on_event(mut self, f: impl Fn(&B::Event, &State) -> Option<M> + Send + 'static) -> Self {
+        self.event_mapper = Some(Box::new(f));
+        self
+    }
+
+    /// Like [`App::on_event`], but with terminal events in the same [form](input::Event) for every
+    /// backend, so that `f` doesn't need to change when the backend does.
+    ///
+    /// Events that have no [`input`] form are dropped, as if `f` returned `None`. This replaces
+    /// any function set with [`App::on_event`].
+    pub fn on_input(self, f: impl Fn(&input::Event, &State) -> Option<M> + Send + 'static) -> Self {
+        let input_state = Mutex::new(<B::Event as Event>::InputState::default());
+        self.on_event(move |event, state| {
+            let mut input_state = input_state.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&event.to_input_with(&mut input_state)?, state)
+        })
+    }
+
+    /// Coalesce consecutive terminal events of the same kind before they reach the updater.
+    ///
+    /// When several such events are ready at once, as during a resize storm or a fast mouse
+    /// movement, only the last one is delivered. See [`Event::coalesces_with`] for which events
+    /// are considered to be of the same kind.
+    pub fn coalesce_events(mut self) -> Self {
+        self.coalesce_events = true;
+        self
+    }
+
+    /// Render at most `fps` frames per second.
+    ///
+    /// Renders requested within one frame interval of the previous one are coalesced into a
+    /// single render at the end of that interval, so bursts of updates don't each trigger a full
+    /// view pass. A render requested after the application has been idle for at least one
+    /// interval still happens immediately, keeping input latency low.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is zero.
+    pub fn max_fps(mut self, fps: u32) -> Self
+    where
+        R: Time + 'static,
+    {
+        assert!(fps > 0, "the frame rate cap must be positive");
+        self.frame_limit = Some(render::FrameLimit {
+            interval: Duration::from_secs(1) / fps,
+            sleep_until: |deadline| R::sleep_until(deadline).boxed_local(),
+        });
+        self
+    }
+
+    /// Set the maximum number of updates applied in a single batch before rendering.
+    ///
+    /// After waiting for an update, the main loop applies every other update that is already
+    /// ready, up to this many in total, and then renders once if any of them asked it to. A
+    /// budget of 1 renders after every update. Defaults to 64.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `budget` is zero.
+    pub fn batch_budget(mut self, budget: usize) -> Self {
+        assert!(budget > 0, "the batch budget must be positive");
+        self.batch_budget = budget;
+        self
+    }
+
+    /// Call `f` with statistics about each batch of updates, for tuning.
+    pub fn on_batch(mut self, f: impl FnMut(&BatchMetrics) + Send + 'static) -> Self {
+        self.on_batch = Some(Box::new(f));
+        self
+    }
+
+    /// Re-render whenever the state's hash changes, whether or not the updater asked for it.
+    ///
+    /// The state is hashed once at the end of each batch of updates and compared to its hash as
+    /// of the last render. With this enabled, the render flag returned by the updater is only
+    /// needed for changes that don't affect the hash, so it can usually be left `false`.
+    ///
+    /// See [`App::fingerprint`] to use something cheaper than hashing the whole state.
+    pub fn render_on_change(self) -> Self
+    where
+        State: Hash,
+    {
+        self.fingerprint(|state| {
+            let mut hasher = DefaultHasher::new();
+            state.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+
+    /// Re-render whenever `f` returns a different value, whether or not the updater asked for it.
+    ///
+    /// `f` is called once at the end of each batch of updates and its result compared to the
+    /// value as of the last render. It should be cheap, such as a version counter bumped on every
+    /// change. Like with [`App::render_on_change`], the render flag returned by the updater is
+    /// then only needed for changes that `f` doesn't capture.
+    pub fn fingerprint(mut self, f: impl Fn(&State) -> u64 + Send + 'static) -> Self {
+        self.fingerprint = Some(Box::new(f));
+        self
+    }
+
+    /// Render on a dedicated thread, so that a slow view never delays input.
+    ///
+    /// Updates keep being applied on the main loop while a frame renders. Each frame is rendered
+    /// from a clone of the state taken when it was requested, and any changes the viewer makes to
+    /// that clone are discarded. If several frames are requested while the thread is busy, only
+    /// the latest is rendered. The render time reported through [`App::on_batch`] is then the
+    /// time taken to clone the state.
+    pub fn render_thread(mut self) -> Self
+    where
+        State: Clone + Send + 'static,
+        V: Send + Sync + 'static,
+        B: Send + 'static,
+        R: 'static,
+    {
+        self.render_thread = Some(render::RenderThread::spawn::<B, R, V>);
+        self
+    }
+
+    /// Set the area of the terminal the interface is rendered to.
+    ///
+    /// By default, the interface takes up the whole terminal on the alternate screen. With
+    /// [`Viewport::Inline`] it is instead drawn under the cursor on the main screen, taking up the
+    /// given number of lines, and [`Task::insert_before`] can print lines above it that stay in
+    /// the scrollback. With [`Viewport::Fixed`] it is drawn in the given area of the main screen.
+    ///
+    /// Either way, the cursor is left on the line below the interface once the application exits.
+    pub fn viewport(mut self, viewport: Viewport) -> Self {
+        self.terminal_options.viewport = viewport;
+        self
+    }
+
+    /// Set which terminal of the process the interface is rendered to.
+    ///
+    /// By default, the interface is rendered to standard output, or to the controlling terminal
+    /// if standard output isn't a terminal. Rendering to [standard
+    /// error](backend::RenderTarget::Stderr) or the [controlling
+    /// terminal](backend::RenderTarget::Tty) instead leaves standard output free for the program,
+    /// as in `result=$(my-picker)`. Input is always read from the terminal.
+    ///
+    /// Ignored when running in a [tty](App::tty).
+    #[cfg(unix)]
+    pub fn render_to(mut self, target: backend::RenderTarget) -> Self {
+        self.terminal_options.render_to = target;
+        self
+    }
+
+    /// Run the application in `tty` instead of the terminal of the process, such as a
+    /// pseudoterminal or a socket serving a remote user.
+    ///
+    /// Only supported by termion, and by termwiz for terminal devices: see
+    /// [`Tty`](backend::Tty) for which backend supports which kind of `Tty`. This fails with
+    /// [`Error::Init`] if the backend can't run in `tty`. With a
+    /// [`DynBackend`](backend::DynBackend), choose the [backend](App::backend) first, as `tty`
+    /// is checked against the backend chosen so far.
+    #[cfg(unix)]
+    pub fn tty(mut self, tty: backend::Tty) -> Result<Self, Error> {
+        B::check_tty(&tty, &self.terminal_options).map_err(Error::Init)?;
+        self.terminal_options.tty = Some(tty);
+        Ok(self)
+    }
+
+    /// Set how the terminal is set up while the application runs, such as whether mouse events
+    /// are reported.
+    ///
+    /// This includes the [viewport](App::viewport), the [render target](App::render_to), the
+    /// [tty](App::tty) and the [backend](App::backend) of a [`DynBackend`](backend::DynBackend),
+    /// so set those through `options` or after calling this.
+    pub fn terminal_options(mut self, options: TerminalOptions) -> Self {
+        self.terminal_options = options;
+        self
+    }
+
+    /// Call `f` whenever the application starts running, right before the first frame is
+    /// rendered.
+    ///
+    /// `f` can return anything an update function can, such as a [`Task`] to start loading data
+    /// in the background.
+    pub fn with_init<O>(mut self, mut f: impl FnMut(&mut State) -> O + Send + 'static) -> Self
+    where
+        O: IntoOutcome<M, Exit = U::Exit>,
+    {
+        self.init = Some(Box::new(move |state| f(state).into_outcome()));
+        self
+    }
+
+    /// Call `f` whenever the application becomes idle: once every update that was ready has been
+    /// applied and the interface rendered, right before waiting for the next update.
+    ///
+    /// `f` can return anything an update function can. If it asks for a render, the interface is
+    /// rendered again before waiting, but `f` isn't called again until after the next update.
+    pub fn on_idle<O>(mut self, mut f: impl FnMut(&mut State) -> O + Send + 'static) -> Self
+    where
+        O: IntoOutcome<M, Exit = U::Exit>,
+    {
+        self.on_idle = Some(Box::new(move |state| f(state).into_outcome()));
+        self
+    }
+
+    /// Call `f` with the new width and height of the terminal whenever it is resized, before the
+    /// resize event reaches the updater.
+    pub fn on_resize(mut self, f: impl FnMut(&mut State, u16, u16) + Send + 'static) -> Self {
+        self.on_resize = Some(Box::new(f));
         self
     }
 
+    /// Call `f` whenever the application stops running, whether it quit or failed, before the
+    /// terminal is restored. This includes failing to read input right after the terminal was set
+    /// up, but not failing to set it up in the first place, as nothing ran yet.
+    pub fn on_exit(mut self, f: impl FnMut(&mut State) + Send + 'static) -> Self {
+        self.on_exit = Some(Box::new(f));
+        self
+    }
+
+    /// Get a handle that can be used to interact with the application from other threads.
+    pub fn handle(&self) -> AppHandle<M> {
+        let tx = self.tx.clone();
+        AppHandle::new(
+            Arc::new(move |message| tx.send(message).is_ok()),
+            self.control.clone(),
+        )
+    }
+
+    /// Get the application's state.
+    ///
+    /// Once [`App::run`] returns, this is the state the application quit with.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Get a mutable reference to the application's state, such as to reset it before running
+    /// the application again.
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.state
+    }
+
+    /// Consume the application, returning its state.
+    pub fn into_state(self) -> State {
+        self.state
+    }
+
     /// Run the application.
-    pub fn run(self) -> std::io::Result<()> {
-        let terminal = B::init();
-        let res = self.executor.clone().block_on(self.run_inner(terminal));
-        B::restore();
+    ///
+    /// Returns how the application stopped running, including the value it exited with through
+    /// [`Outcome::quit_with`], if any.
+    ///
+    /// Fails if the terminal can't be set up, as when there is no terminal to run in, if it
+    /// breaks while the application runs, or if a task panics. See [`Error`] for every way this
+    /// can fail.
+    ///
+    /// The terminal is set up when this is called and restored before it returns, so the
+    /// application can be run again afterwards, as can other applications. A new run picks up
+    /// where the last one left off: it starts from the current state, and keeps receiving
+    /// messages from tasks that were still running and from subscriptions that haven't ended.
+    ///
+    /// This blocks on the application's executor, which some runtimes (such as tokio) don't
+    /// allow from within an async context. Use [`App::run_async`] there instead. An application
+    /// built [with a spawner](AppWithBackend::with_spawner) has no executor, and blocks the
+    /// current thread instead.
+    pub fn run(&mut self) -> Result<Exit<U::Exit>, Error> {
+        self.spawner.clone().block_on(self.run_async())
+    }
+
+    /// Run the application on the async runtime this is awaited on, as with [`App::run`].
+    ///
+    /// Tasks are still spawned on the application's executor, which can be shared with the rest
+    /// of a program through [`AppWithBackend::with_executor`], or handed to the function given to
+    /// [`AppWithBackend::with_spawner`] to run them on this same runtime. The returned future isn't
+    /// `Send`, so it should be awaited directly rather than spawned.
+    pub async fn run_async(&mut self) -> Result<Exit<U::Exit>, Error> {
+        let options = self.terminal_options.clone();
+        let terminal = B::init(&options).map_err(Error::Init)?;
+        // Restores the terminal even if the application panics.
+        let guard = guard::TerminalGuard::new(B::restore, options);
+        let running = self.control.run();
+        let res = self.run_inner(terminal).await;
+        drop(running);
+        drop(guard);
         res
     }
 
-    async fn run_inner(mut self, mut terminal: Terminal<B>) -> std::io::Result<()> {
-        let subscriptions_tx = self.tx.clone();
-        self.executor
-            .spawn(async move {
-                while let Some(message) = self.subscriptions.next().await {
-                    subscriptions_tx.send(message).unwrap();
-                }
-            })
-            .detach();
-        terminal.draw(|f| self.viewer.view(&mut self.state, f))?;
+    async fn run_inner(&mut self, terminal: Terminal<B>) -> Result<Exit<U::Exit>, Error> {
+        // Created only once the terminal is initialized, as initializing it may involve reading
+        // from it (to find where the cursor is for an inline viewport).
+        let events = match B::EventStream::new(&self.terminal_options) {
+            Ok(events) => events,
+            Err(e) => {
+                self.exited();
+                return Err(Error::Init(e));
+            }
+        };
+        let mut renderer = match self.render_thread {
+            Some(spawn) => render::Renderer::Thread(spawn(
+                terminal,
+                self.viewer.clone(),
+                self.terminal_options.viewport.clone(),
+            )),
+            None => render::Renderer::Local(terminal),
+        };
+        let mut scheduler = render::Scheduler::new(self.frame_limit);
+        let mut res = self.main_loop(&mut renderer, &mut scheduler, events).await;
+        self.exited();
+        // Lines inserted on the way out should still make it to the terminal.
+        if res.is_ok() && !scheduler.inserts.is_empty() {
+            res = self.draw(&mut renderer, &mut scheduler).and(res);
+        }
+        let finished = renderer.finish(&self.terminal_options.viewport);
+        let exit = res?;
+        finished.map_err(Error::Draw)?;
+        Ok(exit)
+    }
+
+    /// Call the exit hook, once the application stopped running in a terminal that is still set
+    /// up.
+    fn exited(&mut self) {
+        if let Some(on_exit) = &mut self.on_exit {
+            on_exit(&mut self.state);
+        }
+    }
+
+    async fn main_loop(
+        &mut self,
+        renderer: &mut render::Renderer<B, State>,
+        scheduler: &mut render::Scheduler,
+        mut events: B::EventStream,
+    ) -> Result<Exit<U::Exit>, Error> {
+        if let Some(outcome) = self.init.as_mut().map(|init| init(&mut self.state))
+            && let ControlFlow::Break(exit) = self.handle_outcome(outcome, scheduler)
+        {
+            return Ok(exit);
+        }
+        self.draw(renderer, scheduler)?;
+        // An event read ahead while coalescing that didn't coalesce with the one before it.
+        let mut pending = None;
         loop {
-            let update = futures::select! {
-                message = self.rx.next() => {
-                    match message {
-                        Some(message) => Update::Message(message),
-                        None => break,
+            let first = match pending.take() {
+                Some(item) => Self::terminal_update(item),
+                // See "Scheduling" in the crate docs before reordering these.
+                None => futures::select_biased! {
+                    _ = future::poll_fn(|cx| self.control.poll_quit(cx)).fuse() => {
+                        ControlFlow::Break(Ok(Exit::Quit))
                     }
-                }
-                e = self.event_stream.next() => match e {
-                    Some(Ok(e)) => Update::Terminal(e),
-                    _ => break,
+                    e = future::poll_fn(|cx| self.control.poll_panic(cx)).fuse() => return Err(e),
+                    e = renderer.failed() => return Err(Error::Draw(e)),
+                    item = events.next() => Self::terminal_update(item),
+                    _ = &mut scheduler.timer => {
+                        self.draw(renderer, scheduler)?;
+                        continue;
+                    }
+                    message = self.inbox.next() => Self::message_update(message),
                 },
             };
-            let resize = if let Update::Terminal(e) = &update {
-                Event::resize(e)
-            } else {
-                None
+
+            let first = match first {
+                ControlFlow::Continue(update) => update,
+                ControlFlow::Break(exit) => return exit,
             };
-            if let Some((width, height)) = &resize {
-                terminal.backend_mut().handle_resize(*width, *height);
+
+            let mut metrics = BatchMetrics::default();
+            let mut should_render = Render::No;
+            let mut next = Some(first);
+            while let Some(mut update) = next.take() {
+                match &mut update {
+                    Update::Terminal(e) => {
+                        if self.coalesce_events {
+                            self.coalesce(&mut events, e, &mut pending).await;
+                        }
+                        metrics.events += 1;
+                    }
+                    Update::Message(_) => metrics.messages += 1,
+                }
+                let start = Instant::now();
+                let render = self.apply(update, renderer, scheduler);
+                metrics.update_time += start.elapsed();
+                match render {
+                    ControlFlow::Continue(render) => should_render = should_render.max(render),
+                    ControlFlow::Break(exit) => return Ok(exit),
+                }
+                if metrics.messages + metrics.events >= self.batch_budget {
+                    metrics.budget_exhausted = true;
+                    break;
+                }
+                next = match self.try_next(&mut events, &mut pending).await {
+                    Some(ControlFlow::Continue(update)) => Some(update),
+                    Some(ControlFlow::Break(exit)) => return exit,
+                    None => None,
+                };
+            }
+
+            let start = Instant::now();
+            if self.request_draw(should_render, renderer, scheduler)? {
+                metrics.render_time = Some(start.elapsed());
+            }
+            if let Some(on_batch) = &mut self.on_batch {
+                on_batch(&metrics);
+            }
+
+            // Nothing else was ready, so the application is about to wait.
+            if !metrics.budget_exhausted
+                && let Some(outcome) = self
+                    .on_idle
+                    .as_mut()
+                    .map(|on_idle| on_idle(&mut self.state))
+            {
+                match self.handle_outcome(outcome, scheduler) {
+                    ControlFlow::Continue(render) => {
+                        self.request_draw(render, renderer, scheduler)?;
+                    }
+                    ControlFlow::Break(exit) => return Ok(exit),
+                }
             }
-            let out = self.updater.update(&mut self.state, update);
-            let task = out.0;
-            let should_render = resize.is_some() || out.1;
+        }
+    }
+
+    /// Render if `render` asks for it or the state's fingerprint changed, unless the frame is
+    /// deferred by the frame limit. Returns whether it rendered.
+    fn request_draw(
+        &mut self,
+        render: Render,
+        renderer: &mut render::Renderer<B, State>,
+        scheduler: &mut render::Scheduler,
+    ) -> Result<bool, Error> {
+        let changed = self
+            .fingerprint
+            .as_ref()
+            .is_some_and(|fingerprint| Some(fingerprint(&self.state)) != scheduler.fingerprint);
+        scheduler.clear |= render == Render::Clear;
+        if (render != Render::No || changed) && scheduler.request() {
+            self.draw(renderer, scheduler)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn draw(
+        &mut self,
+        renderer: &mut render::Renderer<B, State>,
+        scheduler: &mut render::Scheduler,
+    ) -> Result<(), Error> {
+        let inserts = std::mem::take(&mut scheduler.inserts);
+        renderer
+            .draw(&*self.viewer, &mut self.state, scheduler.clear, inserts)
+            .map_err(Error::Draw)?;
+        scheduler.rendered(self.fingerprint.as_ref().map(|f| f(&self.state)));
+        Ok(())
+    }
+
+    /// Get the next update that is ready without waiting for one, in priority order.
+    ///
+    /// Sources are polled with the current task's waker rather than through `now_or_never`, as
+    /// some event streams (such as crossterm's) only register the first waker they are given.
+    ///
+    /// Returns `None` if nothing is ready.
+    async fn try_next(
+        &mut self,
+        events: &mut B::EventStream,
+        pending: &mut Option<EventItem<B, R>>,
+    ) -> Option<Next<M, B::Event, U::Exit>> {
+        if future::poll_fn(|cx| Poll::Ready(self.control.poll_quit(cx).is_ready())).await {
+            return Some(ControlFlow::Break(Ok(Exit::Quit)));
+        }
+        if let Poll::Ready(e) = future::poll_fn(|cx| Poll::Ready(self.control.poll_panic(cx))).await
+        {
+            return Some(ControlFlow::Break(Err(e)));
+        }
+        if let Some(item) = pending.take() {
+            return Some(Self::terminal_update(item));
+        }
+        if let Poll::Ready(item) = futures::poll!(events.next()) {
+            return Some(Self::terminal_update(item));
+        }
+        match futures::poll!(self.inbox.next()) {
+            Poll::Ready(message) => Some(Self::message_update(message)),
+            Poll::Pending => None,
+        }
+    }
+
+    /// Replace `e` with the events directly following it for as long as they coalesce.
+    async fn coalesce(
+        &mut self,
+        events: &mut B::EventStream,
+        e: &mut B::Event,
+        pending: &mut Option<EventItem<B, R>>,
+    ) {
+        while let Poll::Ready(next) = futures::poll!(events.next()) {
+            match next {
+                Some(Ok(next)) if e.coalesces_with(&next) => *e = next,
+                next => {
+                    *pending = Some(next);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn terminal_update(item: EventItem<B, R>) -> Next<M, B::Event, U::Exit> {
+        match item {
+            Some(Ok(e)) => ControlFlow::Continue(Update::Terminal(e)),
+            Some(Err(e)) => ControlFlow::Break(Err(Error::EventStream(Box::new(e)))),
+            None => ControlFlow::Break(Ok(Exit::InputClosed)),
+        }
+    }
+
+    fn message_update(message: Option<M>) -> Next<M, B::Event, U::Exit> {
+        match message {
+            Some(message) => ControlFlow::Continue(Update::Message(message)),
+            // The application holds a sender of its own, so this never actually happens.
+            None => ControlFlow::Break(Ok(Exit::Quit)),
+        }
+    }
+
+    /// Apply a single update to the state.
+    ///
+    /// Returns how the update requires the interface to be re-rendered, or breaks with how the
+    /// application exits if it should quit.
+    fn apply(
+        &mut self,
+        update: Update<M, B::Event>,
+        renderer: &mut render::Renderer<B, State>,
+        scheduler: &mut render::Scheduler,
+    ) -> ControlFlow<Exit<U::Exit>, Render> {
+        let resize = if let Update::Terminal(e) = &update {
+            Event::resize(e)
+        } else {
+            None
+        };
+        if let Some((width, height)) = resize {
+            renderer.resize::<R>(width, height);
+            if let Some(on_resize) = &mut self.on_resize {
+                on_resize(&mut self.state, width, height);
+            }
+        }
+        let update = match (update, &self.event_mapper) {
+            (Update::Terminal(e), Some(map)) => map(&e, &self.state).map(Update::Message),
+            (update, _) => Some(update),
+        };
+        let outcome = match update {
+            Some(update) => self.updater.update(&mut self.state, update),
+            None => Outcome::none(),
+        };
+        match self.handle_outcome(outcome, scheduler) {
+            ControlFlow::Continue(render) if resize.is_some() => {
+                ControlFlow::Continue(render.max(Render::Yes))
+            }
+            flow => flow,
+        }
+    }
+
+    /// Execute the tasks of an outcome.
+    ///
+    /// Returns how the outcome requires the interface to be re-rendered, or breaks with how the
+    /// application exits if it should quit.
+    fn handle_outcome(
+        &mut self,
+        outcome: Outcome<M, U::Exit>,
+        scheduler: &mut render::Scheduler,
+    ) -> ControlFlow<Exit<U::Exit>, Render> {
+        let Outcome {
+            tasks,
+            render,
+            mut quit,
+        } = outcome;
+        for task in tasks {
             match task {
                 Task::Perform(future) => {
-                    self.executor.spawn(future.run(self.tx.clone())).detach();
+                    self.spawner
+                        .spawn(future.run(self.tx.clone(), self.control.clone()));
                 }
                 Task::None => {}
-                Task::Quit => break,
-            }
-            if should_render {
-                terminal.draw(|f| self.viewer.view(&mut self.state, f))?;
+                Task::Quit => {
+                    quit.get_or_insert(outcome::Quit::Quit);
+                }
+                Task::InsertBefore(text) => scheduler.inserts.push(text),
             }
         }
+        match quit {
+            Some(outcome::Quit::With(value)) => ControlFlow::Break(Exit::Value(value)),
+            Some(outcome::Quit::Quit) => ControlFlow::Break(Exit::Quit),
+            // Inserting lines may clear the viewport, and only happens as part of a render.
+            None if !scheduler.inserts.is_empty() => ControlFlow::Continue(render.max(Render::Yes)),
+            None => ControlFlow::Continue(render),
+        }
+    }
+}
 
-        Ok(())
+#[cfg(all(
+    test,
+    feature = "tokio",
+    any(feature = "crossterm", feature = "termion", feature = "termwiz")
+))]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>() {}
+
+    /// Applications can be built on one thread and run on another.
+    #[test]
+    fn app_is_send() {
+        type Event = <DefaultBackend as Backend<Tokio>>::Event;
+        type Update = fn(&mut (), crate::Update<(), Event>) -> Outcome<()>;
+        type View = fn(&mut (), &mut Frame);
+        assert_send::<App<(), Update, View, DefaultBackend, Tokio>>();
+    }
+
+    /// Closures returning a `(Task, bool)` compile without naming the type of value the
+    /// application can exit with.
+    #[test]
+    fn closure_updaters_infer_their_exit() {
+        type Event = <DefaultBackend as Backend<Tokio>>::Event;
+        let app = AppWithBackend::<Tokio, DefaultBackend>::new(
+            |_: &mut (), update: crate::Update<(), Event>| match update {
+                crate::Update::Message(()) => (Task::Quit, true),
+                crate::Update::Terminal(_) => (Task::None, false),
+            },
+            |_: &mut (), _: &mut Frame| {},
+        );
+        assert!(app.is_ok());
     }
 }
@@ -0,0 +1,143 @@
+use crate::Task;
+
+/// What the runtime should do after an update.
+///
+/// ```
+/// # use ratatui_elm::{Outcome, Task};
+/// # let task = Task::<()>::None;
+/// // Re-render and run a task in the background.
+/// let outcome: Outcome<()> = Outcome::render().task(task);
+/// // Quit, making `run` return the given value.
+/// let outcome: Outcome<(), &str> = Outcome::quit_with("done");
+/// ```
+///
+/// Updaters may also keep returning `(Task<M>, bool)` or just a [`Task`], both of which convert
+/// into an outcome.
+#[must_use]
+pub struct Outcome<M, X = ()> {
+    pub(crate) tasks: Vec<Task<M>>,
+    pub(crate) render: Render,
+    pub(crate) quit: Option<Quit<X>>,
+}
+
+/// How an outcome asks for the interface to be re-rendered.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Render {
+    No,
+    Yes,
+    /// Clear the terminal before re-rendering, discarding ratatui's diffing buffers.
+    Clear,
+}
+
+pub(crate) enum Quit<X> {
+    Quit,
+    With(X),
+}
+
+impl<M, X> Outcome<M, X> {
+    /// Do nothing.
+    pub fn none() -> Self {
+        Self {
+            tasks: Vec::new(),
+            render: Render::No,
+            quit: None,
+        }
+    }
+
+    /// Re-render the interface.
+    pub fn render() -> Self {
+        Self {
+            render: Render::Yes,
+            ..Self::none()
+        }
+    }
+
+    /// Clear the terminal, then re-render the interface from scratch.
+    ///
+    /// Useful if something other than the application wrote to the terminal.
+    pub fn clear() -> Self {
+        Self {
+            render: Render::Clear,
+            ..Self::none()
+        }
+    }
+
+    /// Quit the application, as with [`Task::Quit`].
+    pub fn quit() -> Self {
+        Self {
+            quit: Some(Quit::Quit),
+            ..Self::none()
+        }
+    }
+
+    /// Quit the application, making [`App::run`](crate::App::run) return
+    /// [`Exit::Value`](crate::Exit::Value) with `value`, such as the item picked in a picker.
+    pub fn quit_with(value: X) -> Self {
+        Self {
+            quit: Some(Quit::With(value)),
+            ..Self::none()
+        }
+    }
+
+    /// Also execute `task`. Can be called several times to execute several tasks.
+    pub fn task(mut self, task: Task<M>) -> Self {
+        if !matches!(task, Task::None) {
+            self.tasks.push(task);
+        }
+        self
+    }
+
+    /// Also execute every task in `tasks`.
+    pub fn tasks(self, tasks: impl IntoIterator<Item = Task<M>>) -> Self {
+        tasks.into_iter().fold(self, Self::task)
+    }
+}
+
+impl<M, X> From<(Task<M>, bool)> for Outcome<M, X> {
+    fn from((task, render): (Task<M>, bool)) -> Self {
+        let outcome = if render { Self::render() } else { Self::none() };
+        outcome.task(task)
+    }
+}
+
+impl<M, X> From<Task<M>> for Outcome<M, X> {
+    fn from(task: Task<M>) -> Self {
+        Self::none().task(task)
+    }
+}
+
+/// A value that an update function can return.
+///
+/// This is what lets update functions return an [`Outcome`], a `(Task<M>, bool)` or a bare
+/// [`Task`] interchangeably. Only an [`Outcome`] can quit with a value, through
+/// [`Outcome::quit_with`]. You shouldn't need to implement it yourself.
+pub trait IntoOutcome<M> {
+    /// The value the application can quit with.
+    type Exit;
+
+    fn into_outcome(self) -> Outcome<M, Self::Exit>;
+}
+
+impl<M, X> IntoOutcome<M> for Outcome<M, X> {
+    type Exit = X;
+
+    fn into_outcome(self) -> Outcome<M, X> {
+        self
+    }
+}
+
+impl<M> IntoOutcome<M> for (Task<M>, bool) {
+    type Exit = ();
+
+    fn into_outcome(self) -> Outcome<M> {
+        self.into()
+    }
+}
+
+impl<M> IntoOutcome<M> for Task<M> {
+    type Exit = ();
+
+    fn into_outcome(self) -> Outcome<M> {
+        self.into()
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{Viewer, backend::Backend, guard};
+use futures::{
+    FutureExt,
+    channel::oneshot,
+    future::{self, Fuse, FusedFuture, LocalBoxFuture},
+};
+use ratatui::{Terminal, Viewport, text::Text, widgets::Widget};
+use std::{
+    io,
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// A cap on how often the interface is rendered, set with [`App::max_fps`](crate::App::max_fps).
+#[derive(Clone, Copy)]
+pub(crate) struct FrameLimit {
+    pub(crate) interval: Duration,
+    /// The runtime's timer, captured where the runtime is known to have one.
+    pub(crate) sleep_until: fn(Instant) -> LocalBoxFuture<'static, Instant>,
+}
+
+/// Decides when a requested frame is actually rendered.
+///
+/// Without a [`FrameLimit`], every request renders immediately. With one, a request renders
+/// immediately if at least one frame interval has passed since the last render (so input after an
+/// idle period is never delayed). Otherwise, the frame is marked dirty and [`Self::timer`]
+/// resolves once it is due, coalescing every request made in the meantime into a single render.
+pub(crate) struct Scheduler {
+    limit: Option<FrameLimit>,
+    last_render: Option<Instant>,
+    /// The fingerprint of the state as of the last render, if fingerprinting is enabled.
+    pub(crate) fingerprint: Option<u64>,
+    /// Whether the terminal should be cleared before the next render.
+    pub(crate) clear: bool,
+    /// Text to insert above the viewport before the next render.
+    pub(crate) inserts: Vec<Text<'static>>,
+    /// Resolves when a dirty frame is due. Terminated while there is no dirty frame.
+    pub(crate) timer: Fuse<LocalBoxFuture<'static, Instant>>,
+}
+
+impl Scheduler {
+    pub(crate) fn new(limit: Option<FrameLimit>) -> Self {
+        Self {
+            limit,
+            last_render: None,
+            fingerprint: None,
+            clear: false,
+            inserts: Vec::new(),
+            timer: Fuse::terminated(),
+        }
+    }
+
+    /// Request a frame. Returns whether it should be rendered right away.
+    pub(crate) fn request(&mut self) -> bool {
+        let (Some(limit), Some(last_render)) = (self.limit, self.last_render) else {
+            return true;
+        };
+        let due = last_render + limit.interval;
+        if Instant::now() >= due {
+            return true;
+        }
+        if self.timer.is_terminated() {
+            self.timer = (limit.sleep_until)(due).fuse();
+        }
+        false
+    }
+
+    /// Record that a frame was just rendered, along with the fingerprint of the rendered state.
+    pub(crate) fn rendered(&mut self, fingerprint: Option<u64>) {
+        self.last_render = Some(Instant::now());
+        self.fingerprint = fingerprint;
+        self.clear = false;
+        self.timer = Fuse::terminated();
+    }
+}
+
+/// Where frames are rendered.
+pub(crate) enum Renderer<B: ratatui::backend::Backend, State> {
+    /// On the main loop, right when they are requested.
+    Local(Terminal<B>),
+    /// On a dedicated thread, from a snapshot of the state.
+    Thread(RenderThread<State>),
+}
+
+impl<B: ratatui::backend::Backend, State> Renderer<B, State> {
+    /// Let the backend know the terminal was resized.
+    ///
+    /// The render thread owns the terminal, so it is told along with the next frame.
+    pub(crate) fn resize<R>(&mut self, width: u16, height: u16)
+    where
+        B: Backend<R>,
+    {
+        match self {
+            Self::Local(terminal) => terminal.backend_mut().handle_resize(width, height),
+            Self::Thread(thread) => thread.resize = Some((width, height)),
+        }
+    }
+
+    /// Render `state` after inserting `inserts` above the viewport, clearing the terminal first
+    /// if `clear` is set.
+    pub(crate) fn draw<V: Viewer<State>>(
+        &mut self,
+        viewer: &V,
+        state: &mut State,
+        clear: bool,
+        inserts: Vec<Text<'static>>,
+    ) -> io::Result<()> {
+        match self {
+            Self::Local(terminal) => {
+                for text in inserts {
+                    insert_before(terminal, text)?;
+                }
+                if clear {
+                    terminal.clear()?;
+                }
+                terminal.draw(|f| viewer.view(state, f))?;
+            }
+            Self::Thread(thread) => thread.submit(state, clear, inserts),
+        }
+        Ok(())
+    }
+
+    /// Stop rendering, leaving the cursor below an inline or fixed viewport.
+    pub(crate) fn finish(self, viewport: &Viewport) -> io::Result<()> {
+        match self {
+            Self::Local(mut terminal) => leave(&mut terminal, viewport),
+            // The thread leaves the viewport itself once it stops.
+            Self::Thread(thread) => thread.stop(),
+        }
+    }
+
+    /// Resolves with the error that stopped the render thread. Never resolves when rendering
+    /// locally, as errors are returned by [`Self::draw`] instead.
+    pub(crate) fn failed(&mut self) -> impl FusedFuture<Output = io::Error> + '_ {
+        async move {
+            match self {
+                Self::Local(_) => future::pending().await,
+                Self::Thread(thread) => (&mut thread.failed)
+                    .await
+                    .unwrap_or_else(|_| io::Error::other("the render thread panicked")),
+            }
+        }
+        .fuse()
+    }
+}
+
+/// Print `text` above an inline viewport. Does nothing for other viewports.
+fn insert_before<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    text: Text<'static>,
+) -> io::Result<()> {
+    let height = u16::try_from(text.height()).unwrap_or(u16::MAX);
+    terminal.insert_before(height, |buf| text.render(buf.area, buf))
+}
+
+/// Move the cursor to the line below an inline or fixed viewport, so that whatever is printed
+/// after the application exits doesn't overwrite it.
+fn leave<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    viewport: &Viewport,
+) -> io::Result<()> {
+    if *viewport == Viewport::Fullscreen {
+        return Ok(());
+    }
+    let area = terminal.get_frame().area();
+    terminal.set_cursor_position((0, area.bottom().saturating_sub(1)))?;
+    let backend = terminal.backend_mut();
+    backend.append_lines(1)?;
+    ratatui::backend::Backend::flush(backend)
+}
+
+/// A frame waiting to be rendered by the render thread.
+struct Snapshot<State> {
+    state: State,
+    resize: Option<(u16, u16)>,
+    clear: bool,
+    inserts: Vec<Text<'static>>,
+}
+
+struct Slot<State> {
+    snapshot: Option<Snapshot<State>>,
+    stopped: bool,
+}
+
+/// A thread that renders snapshots of the state, set up with
+/// [`App::render_thread`](crate::App::render_thread).
+///
+/// Only the latest snapshot is kept: if the thread is still busy rendering when several frames
+/// are submitted, all but the last are skipped.
+pub(crate) struct RenderThread<State> {
+    slot: Arc<(Mutex<Slot<State>>, Condvar)>,
+    snapshot: fn(&State) -> State,
+    /// The latest resize not yet handed to the thread.
+    resize: Option<(u16, u16)>,
+    failed: oneshot::Receiver<io::Error>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<State: Clone + Send + 'static> RenderThread<State> {
+    pub(crate) fn spawn<B, R, V>(
+        mut terminal: Terminal<B>,
+        viewer: Arc<V>,
+        viewport: Viewport,
+    ) -> Self
+    where
+        B: Backend<R> + Send + 'static,
+        R: 'static,
+        V: Viewer<State> + Send + Sync + 'static,
+    {
+        let slot = Arc::new((
+            Mutex::new(Slot {
+                snapshot: None,
+                stopped: false,
+            }),
+            Condvar::new(),
+        ));
+        let (tx, failed) = oneshot::channel();
+        let guard = guard::current();
+        let thread = std::thread::spawn({
+            let slot = slot.clone();
+            move || {
+                // A panic while rendering restores the terminal, as it would on the main thread.
+                guard::enter(guard);
+                let mut render = || -> io::Result<()> {
+                    while let Some(mut snapshot) = Self::wait(&slot) {
+                        if let Some((width, height)) = snapshot.resize {
+                            terminal.backend_mut().handle_resize(width, height);
+                        }
+                        for text in snapshot.inserts {
+                            insert_before(&mut terminal, text)?;
+                        }
+                        if snapshot.clear {
+                            terminal.clear()?;
+                        }
+                        terminal.draw(|f| viewer.view(&mut snapshot.state, f))?;
+                    }
+                    leave(&mut terminal, &viewport)
+                };
+                if let Err(e) = render() {
+                    let _ = tx.send(e);
+                }
+            }
+        });
+        Self {
+            slot,
+            snapshot: State::clone,
+            resize: None,
+            failed,
+            thread: Some(thread),
+        }
+    }
+
+    /// Wait for the next snapshot, or `None` once the thread should stop.
+    fn wait(slot: &(Mutex<Slot<State>>, Condvar)) -> Option<Snapshot<State>> {
+        let (slot, ready) = slot;
+        let mut slot = ready
+            .wait_while(slot.lock().unwrap(), |slot| {
+                slot.snapshot.is_none() && !slot.stopped
+            })
+            .unwrap();
+        // A snapshot submitted right before stopping is still rendered, so that nothing inserted
+        // above the viewport on the way out is lost.
+        slot.snapshot.take()
+    }
+}
+
+impl<State> RenderThread<State> {
+    /// Hand a snapshot of `state` to the thread, replacing any it hasn't started rendering yet.
+    fn submit(&mut self, state: &State, clear: bool, mut inserts: Vec<Text<'static>>) {
+        let state = (self.snapshot)(state);
+        let (slot, ready) = &*self.slot;
+        let mut slot = slot.lock().unwrap();
+        // A replaced snapshot may have carried a resize, a clear or insertions that still need
+        // handling.
+        let (resize, clear) = match slot.snapshot.take() {
+            Some(mut old) => {
+                old.inserts.append(&mut inserts);
+                inserts = old.inserts;
+                (self.resize.take().or(old.resize), clear || old.clear)
+            }
+            None => (self.resize.take(), clear),
+        };
+        slot.snapshot = Some(Snapshot {
+            state,
+            resize,
+            clear,
+            inserts,
+        });
+        ready.notify_one();
+    }
+
+    /// Stop the thread once it rendered the last snapshot, returning the error it stopped with,
+    /// if any.
+    fn stop(mut self) -> io::Result<()> {
+        let panicked = self.join().is_err();
+        match self.failed.try_recv() {
+            Ok(Some(e)) => Err(e),
+            _ if panicked => Err(io::Error::other("the render thread panicked")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Tell the thread to stop, and wait for it to.
+    fn join(&mut self) -> std::thread::Result<()> {
+        let (slot, ready) = &*self.slot;
+        slot.lock().unwrap_or_else(|e| e.into_inner()).stopped = true;
+        ready.notify_one();
+        self.thread.take().map_or(Ok(()), JoinHandle::join)
+    }
+}
+
+impl<State> Drop for RenderThread<State> {
+    fn drop(&mut self) {
+        // A panic has already been reported through `failed`.
+        let _ = self.join();
+    }
+}
@@ -0,0 +1,446 @@
+//! Ready-made subscriptions and utilities for building them.
+//!
+//! The stream combinators in this module use the runtime's timer, so they are only available
+//! for runtimes that provide one (tokio and smol).
+
+use byor::{
+    channel::mpsc::{RuntimeMpsc, UnboundedSender},
+    time::Time,
+};
+use futures::{
+    FutureExt, Stream, StreamExt, pin_mut,
+    stream::{self, BoxStream},
+};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, IsTerminal},
+    time::Duration,
+};
+
+/// Stream the lines of standard input.
+///
+/// This is meant for programs that are fed data through a pipe, as in `find . | my-picker`. The
+/// backends read input from the controlling terminal whenever standard input isn't one, so the
+/// interface stays interactive while this stream reads the pipe.
+///
+/// If standard input is a terminal, the stream ends immediately so as not to fight the backend
+/// over keypresses.
+pub fn stdin_lines<R: RuntimeMpsc>() -> impl Stream<Item = std::io::Result<String>>
+where
+    R::UnboundedSender<std::io::Result<String>>: Send + 'static,
+{
+    let (tx, rx) = R::unbounded_channel();
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        std::thread::spawn(move || {
+            for line in stdin.lock().lines() {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    rx
+}
+
+/// Only emit an item once `duration` has passed without the stream producing another one.
+///
+/// Each new item replaces the one waiting to be emitted, so only the last item of a burst is
+/// delivered. If the stream ends while an item is waiting, that item is emitted immediately.
+pub fn debounce<R: Time, S: Stream>(stream: S, duration: Duration) -> impl Stream<Item = S::Item> {
+    stream::unfold(Box::pin(stream.fuse()), move |mut stream| async move {
+        let mut pending = stream.next().await?;
+        loop {
+            let sleep = R::sleep(duration).fuse();
+            pin_mut!(sleep);
+            futures::select! {
+                item = stream.next() => match item {
+                    Some(item) => pending = item,
+                    None => return Some((pending, stream)),
+                },
+                _ = sleep => return Some((pending, stream)),
+            }
+        }
+    })
+}
+
+/// Emit at most one item per `duration`.
+///
+/// The first item of a burst is emitted immediately. The most recent item received during the
+/// following `duration` is emitted when it elapses, and all others are dropped.
+pub fn throttle<R: Time, S: Stream>(stream: S, duration: Duration) -> impl Stream<Item = S::Item> {
+    let state = (Box::pin(stream.fuse()), false);
+    stream::unfold(state, move |(mut stream, mut throttled)| async move {
+        loop {
+            if !throttled {
+                let item = stream.next().await?;
+                return Some((item, (stream, true)));
+            }
+            let mut pending = None;
+            let sleep = R::sleep(duration).fuse();
+            pin_mut!(sleep);
+            loop {
+                futures::select! {
+                    item = stream.next() => match item {
+                        Some(item) => pending = Some(item),
+                        None => return pending.map(|item| (item, (stream, false))),
+                    },
+                    _ = sleep => break,
+                }
+            }
+            match pending {
+                Some(item) => return Some((item, (stream, true))),
+                None => throttled = false,
+            }
+        }
+    })
+}
+
+/// Collect items for `window` after the first one arrives, keeping only the most recent item for
+/// each key, then emit them all in the order their keys were first seen.
+///
+/// This is useful for bursty sources such as file watchers, where many events for the same path
+/// arrive in quick succession and only the last one matters.
+pub fn coalesce_by_key<R: Time, S: Stream, K: PartialEq>(
+    stream: S,
+    window: Duration,
+    key: impl Fn(&S::Item) -> K,
+) -> impl Stream<Item = S::Item> {
+    let state = (Box::pin(stream.fuse()), VecDeque::new(), key);
+    stream::unfold(state, move |(mut stream, mut ready, key)| async move {
+        if let Some(item) = ready.pop_front() {
+            return Some((item, (stream, ready, key)));
+        }
+        let first = stream.next().await?;
+        let mut batch = vec![(key(&first), first)];
+        let sleep = R::sleep(window).fuse();
+        pin_mut!(sleep);
+        loop {
+            futures::select! {
+                item = stream.next() => match item {
+                    Some(item) => {
+                        let k = key(&item);
+                        match batch.iter_mut().find(|(other, _)| *other == k) {
+                            Some(slot) => slot.1 = item,
+                            None => batch.push((k, item)),
+                        }
+                    }
+                    None => break,
+                },
+                _ = sleep => break,
+            }
+        }
+        ready.extend(batch.into_iter().map(|(_, item)| item));
+        let item = ready.pop_front()?;
+        Some((item, (stream, ready, key)))
+    })
+}
+
+/// What to do when the stream behind a [`Subscription`] ends.
+///
+/// A stream that ends without producing anything, as a watcher that fails to start does, is only
+/// re-created [`Restart::MIN_DELAY`] after it ends at the earliest, so that it doesn't keep the
+/// application busy restarting it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Restart {
+    /// Let the subscription end for good.
+    #[default]
+    Never,
+    /// Re-create the stream immediately.
+    Always,
+    /// Re-create the stream after a delay, starting at `initial` and doubling on each consecutive
+    /// restart up to `max`. The delay is reset once the stream produces an item.
+    Backoff { initial: Duration, max: Duration },
+}
+
+impl Restart {
+    /// The shortest delay before re-creating a stream that ended without producing anything.
+    pub const MIN_DELAY: Duration = Duration::from_millis(100);
+
+    /// How long to wait before re-creating a stream that just ended, given the last backoff delay
+    /// used since it last produced anything, or `None` if it shouldn't be.
+    fn delay(self, last: Option<Duration>) -> Option<Duration> {
+        match self {
+            Restart::Never => None,
+            Restart::Always => Some(Duration::ZERO),
+            Restart::Backoff { initial, max } => {
+                Some(last.map_or(initial, |last| last.saturating_mul(2)).min(max))
+            }
+        }
+    }
+}
+
+/// A long-lived subscription built from a stream factory.
+///
+/// Unlike a plain stream passed to [`App::subscription`](crate::App::subscription), a
+/// subscription can notify the application when its stream ends and re-create it according to a
+/// [`Restart`] policy, so that watchers survive transient failures. Add it to an application with
+/// [`App::subscribe`](crate::App::subscribe).
+pub struct Subscription<M> {
+    factory: Box<dyn FnMut() -> BoxStream<'static, M> + Send>,
+    on_end: Option<Box<dyn Fn() -> M + Send>>,
+    restart: Restart,
+}
+
+impl<M: 'static> Subscription<M> {
+    /// Create a subscription that gets its stream from `factory`.
+    ///
+    /// The factory is called once when the application starts, and again on each restart.
+    pub fn new<S: Stream<Item = M> + Send + 'static>(
+        mut factory: impl FnMut() -> S + Send + 'static,
+    ) -> Self {
+        Self {
+            factory: Box::new(move || factory().boxed()),
+            on_end: None,
+            restart: Restart::Never,
+        }
+    }
+
+    /// Send `message` to the application every time the stream ends.
+    pub fn on_end(mut self, message: M) -> Self
+    where
+        M: Clone + Send,
+    {
+        self.on_end = Some(Box::new(move || message.clone()));
+        self
+    }
+
+    /// Set what to do when the stream ends. Defaults to [`Restart::Never`].
+    pub fn restart(mut self, restart: Restart) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    pub(crate) fn into_stream<R: Timer + 'static>(self) -> BoxStream<'static, M> {
+        let state = Lifecycle {
+            stream: None,
+            restart_in: Some(Duration::ZERO),
+            backoff: None,
+            produced: false,
+            subscription: self,
+        };
+        stream::unfold(state, Lifecycle::next::<R>).boxed()
+    }
+}
+
+struct Lifecycle<M> {
+    subscription: Subscription<M>,
+    stream: Option<BoxStream<'static, M>>,
+    /// How long to wait before (re-)creating the stream, or `None` if it shouldn't be.
+    restart_in: Option<Duration>,
+    /// The last backoff delay used, if the stream hasn't produced anything since.
+    backoff: Option<Duration>,
+    /// Whether the current stream has produced anything.
+    produced: bool,
+}
+
+impl<M> Lifecycle<M> {
+    async fn next<R: Timer>(mut self) -> Option<(M, Self)> {
+        loop {
+            let Some(stream) = &mut self.stream else {
+                let delay = self.restart_in.take()?;
+                if !delay.is_zero() {
+                    <R as sealed::Sealed>::sleep(delay).await;
+                }
+                self.stream = Some((self.subscription.factory)());
+                self.produced = false;
+                continue;
+            };
+            if let Some(message) = stream.next().await {
+                self.backoff = None;
+                self.produced = true;
+                return Some((message, self));
+            }
+            self.stream = None;
+            let restart = self.subscription.restart;
+            self.restart_in = restart.delay(self.backoff).map(|delay| {
+                if let Restart::Backoff { .. } = restart {
+                    self.backoff = Some(delay);
+                }
+                if self.produced {
+                    delay
+                } else {
+                    delay.max(Restart::MIN_DELAY)
+                }
+            });
+            if let Some(on_end) = &self.subscription.on_end {
+                return Some((on_end(), self));
+            }
+        }
+    }
+}
+
+/// A runtime whose timer can restart a [`Subscription`]: tokio or smol.
+///
+/// Unlike [`Time`] alone, this promises that the runtime's sleeps can be sent to other threads,
+/// so that an application with subscriptions can still be built on one thread and run on another.
+pub trait Timer: Time + sealed::Sealed {}
+
+impl<R: Time + sealed::Sealed> Timer for R {}
+
+mod sealed {
+    use futures::future::BoxFuture;
+    use std::time::Duration;
+
+    pub trait Sealed {
+        fn sleep(duration: Duration) -> BoxFuture<'static, ()>;
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Sealed for crate::Tokio {
+        fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+            Box::pin(async move {
+                <Self as byor::time::Time>::sleep(duration).await;
+            })
+        }
+    }
+
+    #[cfg(feature = "smol")]
+    impl Sealed for crate::Smol {
+        fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
+            Box::pin(async move {
+                <Self as byor::time::Time>::sleep(duration).await;
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::Tokio;
+    use futures::future::ready;
+    use std::sync::{Arc, Mutex};
+    use tokio::time::{Instant, sleep};
+
+    /// A stream that waits for each delay in milliseconds before yielding its item, and ends once
+    /// it reaches a `None`.
+    fn timed<T>(items: Vec<(u64, Option<T>)>) -> impl Stream<Item = T> {
+        stream::iter(items)
+            .then(|(delay, item)| async move {
+                sleep(Duration::from_millis(delay)).await;
+                item
+            })
+            .take_while(|item| ready(item.is_some()))
+            .filter_map(ready)
+    }
+
+    /// Collect a stream's items along with when they arrived, in milliseconds since the start.
+    async fn collect_timed<S: Stream>(stream: S) -> Vec<(u64, S::Item)> {
+        let start = Instant::now();
+        stream
+            .map(|item| (start.elapsed().as_millis() as u64, item))
+            .collect()
+            .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_emits_the_last_item_of_each_burst() {
+        let items = timed(vec![
+            (0, Some('a')),
+            (50, Some('b')),
+            (300, Some('c')),
+            (50, None),
+        ]);
+        let output = collect_timed(debounce::<Tokio, _>(items, Duration::from_millis(100))).await;
+        assert_eq!(output, [(150, 'b'), (400, 'c')]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_flushes_on_end() {
+        let items = timed(vec![(0, Some('a')), (50, Some('b')), (0, None)]);
+        let output = collect_timed(debounce::<Tokio, _>(items, Duration::from_millis(100))).await;
+        assert_eq!(output, [(50, 'b')]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttle_emits_at_most_once_per_duration() {
+        let items = timed(vec![
+            (0, Some('a')),
+            (30, Some('b')),
+            (30, Some('c')),
+            (190, Some('d')),
+            (150, None),
+        ]);
+        let output = collect_timed(throttle::<Tokio, _>(items, Duration::from_millis(100))).await;
+        assert_eq!(output, [(0, 'a'), (100, 'c'), (250, 'd')]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttle_flushes_on_end() {
+        let items = timed(vec![(0, Some('a')), (30, Some('b')), (30, None)]);
+        let output = collect_timed(throttle::<Tokio, _>(items, Duration::from_millis(100))).await;
+        assert_eq!(output, [(0, 'a'), (60, 'b')]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn coalesce_by_key_keeps_the_last_item_per_key() {
+        let items = timed(vec![
+            (0, Some((1, 'a'))),
+            (10, Some((2, 'b'))),
+            (10, Some((1, 'c'))),
+            (180, Some((3, 'd'))),
+            (10, Some((3, 'e'))),
+            (0, None),
+        ]);
+        let coalesced =
+            coalesce_by_key::<Tokio, _, _>(items, Duration::from_millis(100), |item| item.0);
+        let output = collect_timed(coalesced).await;
+        assert_eq!(output, [(100, (1, 'c')), (100, (2, 'b')), (210, (3, 'e'))]);
+    }
+
+    /// A subscription whose streams yield `items[n]` when created for the `n`th time, or nothing
+    /// once there are no more, along with when each stream was created.
+    fn recorded(
+        items: Vec<Vec<char>>,
+        restart: Restart,
+    ) -> (BoxStream<'static, char>, Arc<Mutex<Vec<u64>>>) {
+        let start = Instant::now();
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let mut items = items.into_iter();
+        let subscription = Subscription::new({
+            let created = created.clone();
+            move || {
+                created
+                    .lock()
+                    .unwrap()
+                    .push(start.elapsed().as_millis() as u64);
+                stream::iter(items.next().unwrap_or_default())
+            }
+        })
+        .on_end('.')
+        .restart(restart);
+        (subscription.into_stream::<Tokio>(), created)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backoff_doubles_up_to_max_and_resets_on_items() {
+        let restart = Restart::Backoff {
+            initial: Duration::from_millis(200),
+            max: Duration::from_millis(500),
+        };
+        let items = vec![vec![], vec![], vec![], vec![], vec!['a'], vec![]];
+        let (stream, created) = recorded(items, restart);
+        let output: String = stream.take(7).collect().await;
+        assert_eq!(output, "....a..");
+        assert_eq!(*created.lock().unwrap(), [0, 200, 600, 1100, 1600, 1800]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn empty_streams_are_not_restarted_immediately() {
+        let (stream, created) = recorded(vec![vec!['a'], vec![]], Restart::Always);
+        let output: String = stream.take(5).collect().await;
+        assert_eq!(output, "a....");
+        assert_eq!(*created.lock().unwrap(), [0, 0, 100, 200]);
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        let restart = Restart::Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::MAX,
+        };
+        assert_eq!(restart.delay(Some(Duration::MAX)), Some(Duration::MAX));
+        assert_eq!(Restart::Never.delay(None), None);
+    }
+}
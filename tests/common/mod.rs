@@ -0,0 +1,82 @@
+//! Runs tests in child processes on pseudoterminals of their own.
+
+#![allow(dead_code, reason = "not every test uses every helper")]
+
+use std::{
+    fs::File,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::process::CommandExt,
+    },
+    process::{Command, Stdio},
+};
+
+/// Set in the child process to the name of the test it runs.
+pub const CHILD: &str = "RATATUI_ELM_TEST_CHILD";
+
+/// Whether this process is the child running the test called `name`.
+pub fn is_child(name: &str) -> bool {
+    std::env::var(CHILD).as_deref() == Ok(name)
+}
+
+/// A command running the test called `name` in a child process, with `follower` as its standard
+/// streams and controlling terminal.
+pub fn child_command(name: &str, follower: &File) -> Command {
+    let mut command = Command::new(std::env::current_exe().unwrap());
+    command
+        .args([name, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD, name)
+        .stdin(Stdio::from(follower.try_clone().unwrap()))
+        .stdout(Stdio::from(follower.try_clone().unwrap()))
+        .stderr(Stdio::from(follower.try_clone().unwrap()));
+    // SAFETY: only async-signal-safe functions are called between fork and exec.
+    unsafe {
+        command.pre_exec(|| {
+            // Make the pseudoterminal the child's controlling terminal, so that it is also what
+            // `/dev/tty` refers to.
+            if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    command
+}
+
+/// Open a new 80x24 pseudoterminal, returning its leader and follower ends.
+pub fn open_pty() -> (File, File) {
+    let (mut leader, mut follower) = (0, 0);
+    let size = libc::winsize {
+        ws_row: 24,
+        ws_col: 80,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: every pointer is valid, and a null name and termios are allowed.
+    let result = unsafe {
+        libc::openpty(
+            &mut leader,
+            &mut follower,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    assert_eq!(result, 0, "{}", std::io::Error::last_os_error());
+    // SAFETY: `openpty` succeeded, so both file descriptors are open and owned by nothing else.
+    unsafe {
+        (
+            OwnedFd::from_raw_fd(leader).into(),
+            OwnedFd::from_raw_fd(follower).into(),
+        )
+    }
+}
+
+pub fn termios(tty: &File) -> libc::termios {
+    // SAFETY: `termios` is plain data, and is fully written by a successful `tcgetattr`.
+    unsafe {
+        let mut termios = std::mem::zeroed();
+        assert_eq!(libc::tcgetattr(tty.as_raw_fd(), &mut termios), 0);
+        termios
+    }
+}
@@ -0,0 +1,221 @@
+//! Checks that an inline viewport is placed under the cursor of the terminal it is rendered to,
+//! even when standard output isn't a terminal, and that the terminal is only asked where its
+//! cursor is while nothing else reads its input.
+
+#![cfg(all(
+    unix,
+    feature = "tokio",
+    any(feature = "crossterm", feature = "termion")
+))]
+
+mod common;
+
+use common::{child_command, is_child, open_pty};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::fd::AsRawFd,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The reply to every request for the cursor position, as from a terminal whose cursor is on the
+/// third row.
+const CURSOR_POSITION: &[u8] = b"\x1b[3;1R";
+
+#[test]
+#[cfg(feature = "crossterm")]
+fn crossterm_asks_the_terminal_it_renders_to_for_the_cursor() {
+    const NAME: &str = "crossterm_asks_the_terminal_it_renders_to_for_the_cursor";
+    if is_child(NAME) {
+        use ratatui::{Frame, Viewport};
+        use ratatui_elm::{
+            AppWithBackend, Messages, Outcome, Tokio,
+            backend::{CrosstermBackend, RenderTarget},
+        };
+        AppWithBackend::<Tokio, CrosstermBackend>::new(
+            Messages(|_: &mut (), _: ()| Outcome::<()>::quit()),
+            |_: &mut (), frame: &mut Frame| frame.render_widget("inline", frame.area()),
+        )
+        .unwrap()
+        .viewport(Viewport::Inline(1))
+        .render_to(RenderTarget::Stderr)
+        .subscription(futures::stream::iter([()]))
+        .run()
+        .unwrap();
+        return;
+    }
+
+    let (leader, follower) = open_pty();
+    let mut command = child_command(NAME, &follower);
+    command.stdout(std::process::Stdio::piped());
+    let mut child = command.spawn().unwrap();
+
+    // Answers every request for the cursor position as a terminal whose cursor is on the third
+    // row.
+    let terminal = thread::spawn(move || {
+        let (mut leader, mut reply) = (leader.try_clone().unwrap(), leader);
+        let (mut output, mut answered) = (Vec::new(), 0);
+        let mut buf = [0; 4096];
+        // Reading fails once every file descriptor of the follower end is closed.
+        while let Ok(n @ 1..) = leader.read(&mut buf) {
+            output.extend_from_slice(&buf[..n]);
+            let asked = String::from_utf8_lossy(&output).matches("\x1b[6n").count();
+            for _ in answered..asked {
+                reply.write_all(CURSOR_POSITION).unwrap();
+            }
+            answered = asked;
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    });
+
+    let mut stdout = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    let status = child.wait().unwrap();
+    drop(command);
+    drop(follower);
+    let output = terminal.join().unwrap();
+
+    assert!(status.success(), "the child failed:\n{stdout}\n{output:?}");
+    assert!(
+        !stdout.contains("\x1b[6n"),
+        "the cursor position was asked on standard output:\n{stdout:?}"
+    );
+    assert!(
+        output.contains("\x1b[3;1Hinline"),
+        "the viewport wasn't drawn under the cursor:\n{output:?}"
+    );
+}
+
+macro_rules! resizes_inline {
+    ($($feature:literal => $name:ident: $backend:ty),* $(,)?) => {$(
+        #[test]
+        #[cfg(feature = $feature)]
+        fn $name() {
+            if is_child(stringify!($name)) {
+                use ratatui::{Frame, Viewport};
+                use ratatui_elm::{
+                    AppWithBackend, Messages, Outcome, Tokio,
+                    input::{Event, Key, KeyCode},
+                };
+                AppWithBackend::<Tokio, $backend>::new(
+                    Messages(|_: &mut (), _: ()| Outcome::<()>::quit()),
+                    |_: &mut (), frame: &mut Frame| {
+                        let width = format!("width:{}", frame.area().width);
+                        frame.render_widget(width, frame.area());
+                    },
+                )
+                .unwrap()
+                .viewport(Viewport::Inline(2))
+                .on_input(|event, _| match event {
+                    Event::Key(Key { code: KeyCode::Char('q'), .. }) => Some(()),
+                    _ => None,
+                })
+                .run()
+                .unwrap();
+                return;
+            }
+            check_resize(stringify!($name));
+        }
+    )*};
+}
+
+resizes_inline! {
+    "crossterm" => crossterm_resizes_inline_without_asking_again:
+        ratatui_elm::backend::CrosstermBackend,
+    "termion" => termion_resizes_inline_without_asking_again:
+        ratatui_elm::backend::TermionBackend,
+}
+
+/// Run the test called `name` in a child process on a new pseudoterminal, resize the
+/// pseudoterminal once the child rendered, and check that it is rendered again, still reads keys,
+/// and only asked where the cursor was once.
+fn check_resize(name: &str) {
+    let (leader, follower) = open_pty();
+    let mut command = child_command(name, &follower);
+    let mut child = command.spawn().unwrap();
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let terminal = thread::spawn({
+        let output = output.clone();
+        let (mut leader, mut reply) = (leader.try_clone().unwrap(), leader.try_clone().unwrap());
+        move || {
+            let mut asked = 0;
+            let mut buf = [0; 4096];
+            // Reading fails once every file descriptor of the follower end is closed.
+            while let Ok(n @ 1..) = leader.read(&mut buf) {
+                let mut output = output.lock().unwrap();
+                output.push_str(&String::from_utf8_lossy(&buf[..n]));
+                let asking = output.matches("\x1b[6n").count();
+                for _ in asked..asking {
+                    reply.write_all(CURSOR_POSITION).unwrap();
+                }
+                asked = asking;
+            }
+            asked
+        }
+    });
+
+    wait_for(&output, "width:80");
+    // Leave the application time to start listening for resizes, which may only happen once it
+    // waits for input.
+    thread::sleep(Duration::from_millis(300));
+    set_size(&leader, 100, 30);
+    wait_for(&output, "width:100");
+    (&leader).write_all(b"q").unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("the child didn't quit:\n{:?}", output.lock().unwrap());
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    drop(command);
+    drop(follower);
+    let asked = terminal.join().unwrap();
+    let output = output.lock().unwrap();
+
+    assert!(status.success(), "the child failed:\n{output:?}");
+    assert_eq!(
+        asked, 1,
+        "the cursor position was asked more than once:\n{output:?}"
+    );
+}
+
+/// Wait for `text` to be written to the terminal.
+fn wait_for(output: &Mutex<String>, text: &str) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !output.lock().unwrap().contains(text) {
+        assert!(
+            Instant::now() < deadline,
+            "{text:?} wasn't written:\n{:?}",
+            output.lock().unwrap()
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Resize the pseudoterminal whose leader end is `leader`, which reports it to the child with
+/// `SIGWINCH`.
+fn set_size(leader: &File, width: u16, height: u16) {
+    let size = libc::winsize {
+        ws_row: height,
+        ws_col: width,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: the file descriptor is valid and `size` is a valid `winsize`.
+    let result = unsafe { libc::ioctl(leader.as_raw_fd(), libc::TIOCSWINSZ, &size) };
+    assert_eq!(result, 0, "{}", std::io::Error::last_os_error());
+}
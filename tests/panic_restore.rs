@@ -0,0 +1,117 @@
+//! Checks that every backend restores the terminal when the application panics, and that a task
+//! panicking doesn't print over the interface.
+//!
+//! Each test runs itself again in a child process, on a pseudoterminal of its own, where the
+//! application panics as soon as it renders, or where a task panics and the child panics with the
+//! error it gets back. Once the child exits, the pseudoterminal must be back out of raw mode and of
+//! the alternate screen, with the panic message printed only after that.
+
+#![cfg(all(unix, feature = "tokio"))]
+
+mod common;
+
+use common::{child_command, is_child, open_pty, termios};
+use std::{io::Read, thread};
+
+const PANIC_MESSAGE: &str = "the view panicked";
+
+const TASK_PANIC_MESSAGE: &str = "the task panicked";
+
+macro_rules! restores_on_panic {
+    ($($feature:literal => $name:ident: $backend:ty),* $(,)?) => {$(
+        #[test]
+        #[cfg(feature = $feature)]
+        fn $name() {
+            if is_child(stringify!($name)) {
+                use ratatui_elm::{AppWithBackend, Messages, Outcome, Tokio};
+                let _ = AppWithBackend::<Tokio, $backend>::new(
+                    Messages(|_: &mut (), _: ()| Outcome::<()>::none()),
+                    |_: &mut (), _: &mut ratatui::Frame| panic!("{PANIC_MESSAGE}"),
+                )
+                .unwrap()
+                .run();
+                unreachable!("the application should have panicked");
+            }
+            check_child(stringify!($name), PANIC_MESSAGE);
+        }
+    )*};
+}
+
+restores_on_panic! {
+    "crossterm" => crossterm_restores_on_panic: ratatui_elm::backend::CrosstermBackend,
+    "termion" => termion_restores_on_panic: ratatui_elm::backend::TermionBackend,
+    "termwiz" => termwiz_restores_on_panic: ratatui_elm::backend::TermwizBackend,
+}
+
+macro_rules! reports_task_panics {
+    ($($feature:literal => $name:ident: $backend:ty),* $(,)?) => {$(
+        #[test]
+        #[cfg(feature = $feature)]
+        fn $name() {
+            if is_child(stringify!($name)) {
+                use ratatui_elm::{AppWithBackend, Messages, Outcome, Task, Tokio};
+                let res = AppWithBackend::<Tokio, $backend>::new(
+                    Messages(|_: &mut (), _: ()| Outcome::<()>::none()),
+                    |_: &mut (), _: &mut ratatui::Frame| {},
+                )
+                .unwrap()
+                .with_init(|_| Task::perform(async { panic!("{TASK_PANIC_MESSAGE}") }))
+                .run();
+                panic!("{}", res.unwrap_err());
+            }
+            check_child(stringify!($name), TASK_PANIC_MESSAGE);
+        }
+    )*};
+}
+
+reports_task_panics! {
+    "crossterm" => crossterm_reports_task_panics: ratatui_elm::backend::CrosstermBackend,
+    "termion" => termion_reports_task_panics: ratatui_elm::backend::TermionBackend,
+    "termwiz" => termwiz_reports_task_panics: ratatui_elm::backend::TermwizBackend,
+}
+
+/// Run the test called `name` in a child process on a new pseudoterminal, and check how it left
+/// that pseudoterminal, and that it only printed `message` once it left the alternate screen.
+fn check_child(name: &str, message: &str) {
+    let (leader, follower) = open_pty();
+    let mut command = child_command(name, &follower);
+    let mut child = command.spawn().unwrap();
+
+    // The child blocks once the pseudoterminal's buffer is full, so read it as the child runs.
+    let output = thread::spawn(move || {
+        let mut leader = leader;
+        let mut output = Vec::new();
+        let mut buf = [0; 4096];
+        // Reading fails once every file descriptor of the follower end is closed.
+        while let Ok(n @ 1..) = leader.read(&mut buf) {
+            output.extend_from_slice(&buf[..n]);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    });
+
+    let status = child.wait().unwrap();
+    let termios = termios(&follower);
+    drop(command);
+    drop(follower);
+    let output = output.join().unwrap();
+
+    assert!(!status.success(), "the child should have panicked");
+    assert!(
+        termios.c_lflag & libc::ICANON != 0 && termios.c_lflag & libc::ECHO != 0,
+        "the terminal was left in raw mode:\n{output:?}"
+    );
+    let entered = output
+        .find("\x1b[?1049h")
+        .unwrap_or_else(|| panic!("the alternate screen wasn't entered:\n{output:?}"));
+    let left = output[entered..]
+        .find("\x1b[?1049l")
+        .map(|left| entered + left)
+        .unwrap_or_else(|| panic!("the alternate screen wasn't left:\n{output:?}"));
+    let message = output
+        .find(message)
+        .unwrap_or_else(|| panic!("the panic message wasn't printed:\n{output:?}"));
+    assert!(
+        message > left,
+        "the panic message was printed to the alternate screen:\n{output:?}"
+    );
+}